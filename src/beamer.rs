@@ -0,0 +1,540 @@
+//! Presentations of the `beamer` class.
+//!
+//! ```
+//! use trylatex::beamer::{Block, Frame, Overlay, Pause};
+//! use trylatex::{Container, Document, DocumentType, Item, Itemize, Text};
+//!
+//! let mut doc = Document::new();
+//! doc.preambule().r#type(DocumentType::Beamer).theme("Madrid");
+//!
+//! let doc = doc.with(
+//!     Frame::new()
+//!         .title("Status")
+//!         .with(Itemize::new().item(Item::new().overlay(Overlay::from_slide(2)).with(Text("Done"))))
+//!         .with(Pause)
+//!         .with(Block::new("Next").with(Text("Release"))),
+//! );
+//! assert!(doc.try_render().unwrap().contains("\\item<2-> Done"));
+//! ```
+
+use std::fmt;
+
+use crate::{Area, Argument, Container, Context, Element, Error, Macros, Parameter, Raw};
+
+/// Slides an element is shown on, e.g. `<2->`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Overlay(String);
+
+impl Overlay {
+    /// Only the slide, `<n>`.
+    pub fn on(slide: usize) -> Self {
+        Self(slide.to_string())
+    }
+
+    /// The slide and the ones after it, `<n->`.
+    pub fn from_slide(slide: usize) -> Self {
+        Self(format!("{}-", slide))
+    }
+
+    /// Slides up to the slide, `<-n>`.
+    pub fn until(slide: usize) -> Self {
+        Self(format!("-{}", slide))
+    }
+
+    /// `<a-b>`
+    pub fn range(first: usize, last: usize) -> Self {
+        Self(format!("{}-{}", first, last))
+    }
+
+    /// Adds the slides of another overlay, e.g. `<1,3->`.
+    pub fn and(mut self, other: Overlay) -> Self {
+        self.0.push(',');
+        self.0.push_str(&other.0);
+        self
+    }
+}
+
+impl Element for Overlay {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        write!(w, "<{}>", self.0)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        require_beamer(ctx, "overlay specification")
+    }
+}
+
+/// Fails unless the document is a presentation.
+pub(crate) fn require_beamer(ctx: &Context, element: &str) -> Result<(), Error> {
+    if ctx.class().has_frames() {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedInClass {
+            element: element.to_owned(),
+            class: ctx.class().to_string(),
+        })
+    }
+}
+
+/// A slide, frames can't be nested.
+pub struct Frame<'a> {
+    title: Option<Parameter>,
+    subtitle: Option<Parameter>,
+    fragile: bool,
+    body: Area<'a>,
+}
+
+impl Frame<'_> {
+    pub fn new() -> Self {
+        Self {
+            title: None,
+            subtitle: None,
+            fragile: false,
+            body: Area::new(),
+        }
+    }
+
+    pub fn title<P: Into<Parameter>>(mut self, title: P) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn subtitle<P: Into<Parameter>>(mut self, subtitle: P) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Lets the frame hold verbatim content, e.g. `\verb` or `lstlisting`.
+    pub fn fragile(mut self) -> Self {
+        self.fragile = true;
+        self
+    }
+}
+
+impl Default for Frame<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for Frame<'a> {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.body = self.body.with(e);
+        self
+    }
+}
+
+impl Element for Frame<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut begin = Macros::new("begin").param("frame");
+        if self.fragile {
+            begin = begin.opt(Raw("fragile"));
+        }
+        begin.render_to(w)?;
+
+        if let Some(title) = &self.title {
+            w.write_char('\n')?;
+            Macros::new("frametitle")
+                .param(title.clone())
+                .render_to(w)?;
+        }
+        if let Some(subtitle) = &self.subtitle {
+            w.write_char('\n')?;
+            Macros::new("framesubtitle")
+                .param(subtitle.clone())
+                .render_to(w)?;
+        }
+
+        w.write_char('\n')?;
+        self.body.render_to(w)?;
+        w.write_char('\n')?;
+        Macros::new("end").param("frame").render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        require_beamer(ctx, "frame")?;
+
+        let depth = ctx.enter("frame");
+        if depth > 1 {
+            return Err(Error::TooDeeplyNested {
+                environment: "frame".to_owned(),
+                depth,
+            });
+        }
+        self.body.visit(ctx)?;
+        ctx.leave("frame");
+
+        Ok(())
+    }
+}
+
+struct TitledBlock<'a> {
+    environment: &'static str,
+    title: Parameter,
+    overlay: Option<Overlay>,
+    body: Area<'a>,
+}
+
+impl Element for TitledBlock<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        Macros::new("begin").param(self.environment).render_to(w)?;
+        if let Some(overlay) = &self.overlay {
+            overlay.render_to(w)?;
+        }
+        Argument::Mandatory(self.title.clone()).render_to(w)?;
+        w.write_char('\n')?;
+        self.body.render_to(w)?;
+        w.write_char('\n')?;
+        Macros::new("end").param(self.environment).render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        require_beamer(ctx, self.environment)?;
+        self.body.visit(ctx)
+    }
+}
+
+macro_rules! block {
+    ($(#[$doc:meta])* $name:ident, $environment:literal) => {
+        $(#[$doc])*
+        pub struct $name<'a>(TitledBlock<'a>);
+
+        impl $name<'_> {
+            pub fn new<P: Into<Parameter>>(title: P) -> Self {
+                Self(TitledBlock {
+                    environment: $environment,
+                    title: title.into(),
+                    overlay: None,
+                    body: Area::new(),
+                })
+            }
+
+            /// Shows the block only on some slides.
+            pub fn overlay(mut self, overlay: Overlay) -> Self {
+                self.0.overlay = Some(overlay);
+                self
+            }
+        }
+
+        impl<'a> Container<'a> for $name<'a> {
+            fn with<E: Element + 'a>(mut self, e: E) -> Self {
+                self.0.body = self.0.body.with(e);
+                self
+            }
+        }
+
+        impl Element for $name<'_> {
+            fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+                self.0.render_to(w)
+            }
+
+            fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+                self.0.visit(ctx)
+            }
+        }
+    };
+}
+
+block!(
+    /// A box with a title, `block`.
+    Block,
+    "block"
+);
+block!(
+    /// A block which stands out, `alertblock`.
+    AlertBlock,
+    "alertblock"
+);
+block!(
+    /// A block for examples, `exampleblock`.
+    ExampleBlock,
+    "exampleblock"
+);
+
+/// A column of [`Columns`].
+pub struct Column<'a> {
+    width: Option<String>,
+    body: Area<'a>,
+}
+
+impl Column<'_> {
+    pub fn new() -> Self {
+        Self {
+            width: None,
+            body: Area::new(),
+        }
+    }
+
+    /// Sets the width, by default the columns share the width evenly.
+    pub fn width<S: AsRef<str>>(mut self, width: S) -> Self {
+        self.width = Some(width.as_ref().to_owned());
+        self
+    }
+}
+
+impl Default for Column<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for Column<'a> {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.body = self.body.with(e);
+        self
+    }
+}
+
+/// Content side by side, `columns`.
+///
+/// Each element added with [`Container::with`] becomes a column.
+pub struct Columns<'a> {
+    columns: Vec<Column<'a>>,
+}
+
+impl<'a> Columns<'a> {
+    pub fn new() -> Self {
+        Self {
+            columns: Vec::new(),
+        }
+    }
+
+    pub fn column(mut self, column: Column<'a>) -> Self {
+        self.columns.push(column);
+        self
+    }
+}
+
+impl Default for Columns<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for Columns<'a> {
+    fn with<E: Element + 'a>(self, e: E) -> Self {
+        self.column(Column::new().with(e))
+    }
+}
+
+impl Element for Columns<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        // leave a bit of space between columns
+        let width = format!("{:.2}\\textwidth", 0.95 / self.columns.len().max(1) as f64);
+
+        Macros::new("begin").param("columns").render_to(w)?;
+        for column in &self.columns {
+            w.write_char('\n')?;
+            Macros::new("begin")
+                .param("column")
+                .param(Raw(column.width.as_deref().unwrap_or(&width)))
+                .render_to(w)?;
+            w.write_char('\n')?;
+            column.body.render_to(w)?;
+            w.write_char('\n')?;
+            Macros::new("end").param("column").render_to(w)?;
+        }
+        w.write_char('\n')?;
+        Macros::new("end").param("columns").render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        require_beamer(ctx, "columns")?;
+        self.columns.iter().try_for_each(|c| c.body.visit(ctx))
+    }
+}
+
+/// Ends the slide, the rest of the frame shows up on the next one, `\pause`.
+pub struct Pause;
+
+impl Element for Pause {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        Macros::new("pause").render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        require_beamer(ctx, "\\pause")
+    }
+}
+
+macro_rules! overlaid {
+    ($(#[$doc:meta])* $name:ident, $command:literal) => {
+        $(#[$doc])*
+        pub struct $name<'a> {
+            overlay: Overlay,
+            content: Box<dyn Element + 'a>,
+        }
+
+        impl<'a> $name<'a> {
+            pub fn new<E: Element + 'a>(overlay: Overlay, content: E) -> Self {
+                Self {
+                    overlay,
+                    content: Box::new(content),
+                }
+            }
+        }
+
+        impl Element for $name<'_> {
+            fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+                w.write_str(concat!("\\", $command))?;
+                self.overlay.render_to(w)?;
+                w.write_char('{')?;
+                self.content.render_to(w)?;
+                w.write_char('}')
+            }
+
+            fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+                require_beamer(ctx, concat!("\\", $command))?;
+                self.content.visit(ctx)
+            }
+        }
+    };
+}
+
+overlaid!(
+    /// Content which is there only on some slides, `\only`.
+    Only,
+    "only"
+);
+overlaid!(
+    /// Content which is visible only on some slides, `\uncover`,
+    /// it takes space on the others.
+    Uncover,
+    "uncover"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, DocumentType, Item, Itemize, Text};
+
+    fn presentation() -> Document<'static> {
+        let mut doc = Document::new();
+        doc.preambule()
+            .r#type(DocumentType::Beamer)
+            .theme("Madrid")
+            .color_theme("beaver");
+        doc
+    }
+
+    #[test]
+    fn render() {
+        let doc = presentation().with(
+            Frame::new()
+                .title("Status")
+                .subtitle("Week 12")
+                .fragile()
+                .with(
+                    Columns::new()
+                        .column(
+                            Column::new().width("0.6\\textwidth").with(
+                                Itemize::new()
+                                    .item(
+                                        Item::new()
+                                            .overlay(Overlay::on(1).and(Overlay::from_slide(3)))
+                                            .with(Text("a")),
+                                    )
+                                    .item(
+                                        Item::new()
+                                            .overlay(Overlay::range(2, 4))
+                                            .label("--")
+                                            .with(Text("b")),
+                                    ),
+                            ),
+                        )
+                        .with(
+                            AlertBlock::new("Risks")
+                                .overlay(Overlay::until(2))
+                                .with(Text("c")),
+                        ),
+                )
+                .with(Pause)
+                .with(Only::new(Overlay::on(2), Text("x")))
+                .with(Uncover::new(
+                    Overlay::from_slide(3),
+                    ExampleBlock::new("").with(Text("y")),
+                )),
+        );
+
+        assert_eq!(
+            r"\documentclass{beamer}
+\usetheme{Madrid}
+\usecolortheme{beaver}
+
+\begin{document}
+\begin{frame}[fragile]
+\frametitle{Status}
+\framesubtitle{Week 12}
+\begin{columns}
+\begin{column}{0.6\textwidth}
+\begin{itemize}
+\item<1,3-> a
+\item<2-4>[--] b
+\end{itemize}
+\end{column}
+\begin{column}{0.47\textwidth}
+\begin{alertblock}<-2>{Risks}
+c
+\end{alertblock}
+\end{column}
+\end{columns}\pause\only<2>{x}\uncover<3->{\begin{exampleblock}{}
+y
+\end{exampleblock}}
+\end{frame}
+\end{document}
+",
+            doc.try_render().unwrap()
+        );
+    }
+
+    #[test]
+    fn requires_beamer() {
+        let doc = Document::new().with(Frame::new());
+        assert_eq!(
+            Err(Error::UnsupportedInClass {
+                element: "frame".to_owned(),
+                class: "article".to_owned(),
+            }),
+            doc.try_render()
+        );
+
+        let doc = Document::new().with(Itemize::new().item(Item::new().overlay(Overlay::on(2))));
+        assert!(doc.try_render().is_err());
+
+        let mut doc = Document::new();
+        doc.preambule().r#type(DocumentType::custom("acmart"));
+        assert!(doc.with(Frame::new()).try_render().is_err());
+
+        let mut doc = Document::new();
+        doc.preambule().r#type(DocumentType::Custom {
+            name: "slides".to_owned(),
+            chapters: false,
+            frames: true,
+        });
+        assert!(doc.with(Frame::new()).try_render().is_ok());
+
+        let mut doc = Document::new();
+        doc.preambule().theme("Madrid");
+        assert_eq!(
+            Err(Error::UnsupportedInClass {
+                element: "\\usetheme".to_owned(),
+                class: "article".to_owned(),
+            }),
+            doc.try_render()
+        );
+
+        let doc = presentation().with(Frame::new().with(Frame::new()));
+        assert_eq!(
+            Err(Error::TooDeeplyNested {
+                environment: "frame".to_owned(),
+                depth: 2,
+            }),
+            doc.try_render()
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! Bibliography entries and `.bib` files.
+
+mod parse;
+
+use std::{fmt, fs, io, path::Path, str::FromStr};
+
+pub use crate::ParseError;
+pub use parse::parse;
+
+/// The kind of a bibliography entry, `@article` and so on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum EntryType {
+    Article,
+    Book,
+    Booklet,
+    InBook,
+    InCollection,
+    InProceedings,
+    Manual,
+    MastersThesis,
+    PhdThesis,
+    Proceedings,
+    TechReport,
+    Unpublished,
+    Misc,
+    /// `@online` of biblatex.
+    Online,
+    Other(String),
+}
+
+impl EntryType {
+    /// Parses the name of an entry type, ignoring case as BibTeX does.
+    pub fn from_name<S: AsRef<str>>(name: S) -> Self {
+        match name.as_ref().to_lowercase().as_str() {
+            "article" => EntryType::Article,
+            "book" => EntryType::Book,
+            "booklet" => EntryType::Booklet,
+            "inbook" => EntryType::InBook,
+            "incollection" => EntryType::InCollection,
+            "inproceedings" | "conference" => EntryType::InProceedings,
+            "manual" => EntryType::Manual,
+            "mastersthesis" => EntryType::MastersThesis,
+            "phdthesis" => EntryType::PhdThesis,
+            "proceedings" => EntryType::Proceedings,
+            "techreport" => EntryType::TechReport,
+            "unpublished" => EntryType::Unpublished,
+            "misc" => EntryType::Misc,
+            "online" => EntryType::Online,
+            other => EntryType::Other(other.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for EntryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryType::Article => f.write_str("article"),
+            EntryType::Book => f.write_str("book"),
+            EntryType::Booklet => f.write_str("booklet"),
+            EntryType::InBook => f.write_str("inbook"),
+            EntryType::InCollection => f.write_str("incollection"),
+            EntryType::InProceedings => f.write_str("inproceedings"),
+            EntryType::Manual => f.write_str("manual"),
+            EntryType::MastersThesis => f.write_str("mastersthesis"),
+            EntryType::PhdThesis => f.write_str("phdthesis"),
+            EntryType::Proceedings => f.write_str("proceedings"),
+            EntryType::TechReport => f.write_str("techreport"),
+            EntryType::Unpublished => f.write_str("unpublished"),
+            EntryType::Misc => f.write_str("misc"),
+            EntryType::Online => f.write_str("online"),
+            EntryType::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+/// A field of a bibliography entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Field {
+    Address,
+    Author,
+    BookTitle,
+    Chapter,
+    Doi,
+    Edition,
+    Editor,
+    HowPublished,
+    Institution,
+    Isbn,
+    Issn,
+    Journal,
+    Month,
+    Note,
+    Number,
+    Organization,
+    Pages,
+    Publisher,
+    School,
+    Series,
+    Title,
+    Type,
+    Url,
+    Volume,
+    Year,
+    Other(String),
+}
+
+impl Field {
+    /// Parses the name of a field, ignoring case as BibTeX does.
+    pub fn from_name<S: AsRef<str>>(name: S) -> Self {
+        match name.as_ref().to_lowercase().as_str() {
+            "address" => Field::Address,
+            "author" => Field::Author,
+            "booktitle" => Field::BookTitle,
+            "chapter" => Field::Chapter,
+            "doi" => Field::Doi,
+            "edition" => Field::Edition,
+            "editor" => Field::Editor,
+            "howpublished" => Field::HowPublished,
+            "institution" => Field::Institution,
+            "isbn" => Field::Isbn,
+            "issn" => Field::Issn,
+            "journal" => Field::Journal,
+            "month" => Field::Month,
+            "note" => Field::Note,
+            "number" => Field::Number,
+            "organization" => Field::Organization,
+            "pages" => Field::Pages,
+            "publisher" => Field::Publisher,
+            "school" => Field::School,
+            "series" => Field::Series,
+            "title" => Field::Title,
+            "type" => Field::Type,
+            "url" => Field::Url,
+            "volume" => Field::Volume,
+            "year" => Field::Year,
+            other => Field::Other(other.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Address => f.write_str("address"),
+            Field::Author => f.write_str("author"),
+            Field::BookTitle => f.write_str("booktitle"),
+            Field::Chapter => f.write_str("chapter"),
+            Field::Doi => f.write_str("doi"),
+            Field::Edition => f.write_str("edition"),
+            Field::Editor => f.write_str("editor"),
+            Field::HowPublished => f.write_str("howpublished"),
+            Field::Institution => f.write_str("institution"),
+            Field::Isbn => f.write_str("isbn"),
+            Field::Issn => f.write_str("issn"),
+            Field::Journal => f.write_str("journal"),
+            Field::Month => f.write_str("month"),
+            Field::Note => f.write_str("note"),
+            Field::Number => f.write_str("number"),
+            Field::Organization => f.write_str("organization"),
+            Field::Pages => f.write_str("pages"),
+            Field::Publisher => f.write_str("publisher"),
+            Field::School => f.write_str("school"),
+            Field::Series => f.write_str("series"),
+            Field::Title => f.write_str("title"),
+            Field::Type => f.write_str("type"),
+            Field::Url => f.write_str("url"),
+            Field::Volume => f.write_str("volume"),
+            Field::Year => f.write_str("year"),
+            Field::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+/// A bibliography entry.
+///
+/// Values are BibTeX code, they're written in braces as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    kind: EntryType,
+    key: String,
+    fields: Vec<(Field, String)>,
+}
+
+impl Entry {
+    pub fn new<S: AsRef<str>>(kind: EntryType, key: S) -> Self {
+        Self {
+            kind,
+            key: key.as_ref().to_owned(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Sets a field, replacing its previous value.
+    pub fn field<S: AsRef<str>>(mut self, field: Field, value: S) -> Self {
+        let value = value.as_ref().to_owned();
+        match self.fields.iter_mut().find(|(f, _)| *f == field) {
+            Some(present) => present.1 = value,
+            None => self.fields.push((field, value)),
+        }
+        self
+    }
+
+    pub fn kind(&self) -> &EntryType {
+        &self.kind
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn get(&self, field: &Field) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(f, _)| f == field)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&Field, &str)> {
+        self.fields.iter().map(|(f, value)| (f, value.as_str()))
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}{{{}", self.kind, self.key)?;
+        for (field, value) in &self.fields {
+            write!(f, ",\n  {} = {{{}}}", field, value)?;
+        }
+        f.write_str("\n}")
+    }
+}
+
+/// A set of entries with distinct keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Bibliography {
+    entries: Vec<Entry>,
+}
+
+impl Bibliography {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds an entry, replacing an entry with the same key.
+    pub fn add(&mut self, entry: Entry) -> &mut Self {
+        match self.entries.iter_mut().find(|e| e.key == entry.key) {
+            Some(present) => *present = entry,
+            None => self.entries.push(entry),
+        }
+        self
+    }
+
+    pub fn get<S: AsRef<str>>(&self, key: S) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.key == key.as_ref())
+    }
+
+    pub fn contains<S: AsRef<str>>(&self, key: S) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Renders the entries as a `.bib` file.
+    pub fn to_bibtex(&self) -> String {
+        let mut buf = String::new();
+        for entry in &self.entries {
+            buf.push_str(&entry.to_string());
+            buf.push_str("\n\n");
+        }
+
+        buf
+    }
+
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_bibtex())
+    }
+}
+
+impl FromStr for Bibliography {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write() {
+        let mut bib = Bibliography::new();
+        bib.add(
+            Entry::new(EntryType::Article, "knuth84")
+                .field(Field::Author, "Donald E. Knuth")
+                .field(Field::Title, "Literate Programming")
+                .field(Field::Year, "1983")
+                .field(Field::Year, "1984"),
+        )
+        .add(
+            Entry::new(EntryType::from_name("MISC"), "web")
+                .field(Field::from_name("URL"), "https://example.com"),
+        );
+
+        assert_eq!(
+            "@article{knuth84,
+  author = {Donald E. Knuth},
+  title = {Literate Programming},
+  year = {1984}
+}
+
+@misc{web,
+  url = {https://example.com}
+}
+
+",
+            bib.to_bibtex()
+        );
+        assert_eq!(Some("1984"), bib.get("knuth84").unwrap().get(&Field::Year));
+    }
+}
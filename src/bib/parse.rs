@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+
+use super::{Bibliography, Entry, EntryType, Field};
+use crate::ParseError;
+
+/// Parses a BibTeX file.
+///
+/// `@string` abbreviations are expanded and `@comment`, `@preamble`
+/// and text between entries are skipped.
+///
+/// ```
+/// use trylatex::bib::{self, Field};
+///
+/// let bib = bib::parse(r#"
+///     @string{ acm = "ACM" }
+///     @article{key, title = {On {TeX}}, publisher = acm # " Press", year = 1984}
+/// "#).unwrap();
+///
+/// let entry = bib.get("key").unwrap();
+/// assert_eq!(Some("On {TeX}"), entry.get(&Field::Title));
+/// assert_eq!(Some("ACM Press"), entry.get(&Field::Publisher));
+/// ```
+pub fn parse(src: &str) -> Result<Bibliography, ParseError> {
+    Parser::new(src).parse()
+}
+
+const MONTHS: &[(&str, &str)] = &[
+    ("jan", "January"),
+    ("feb", "February"),
+    ("mar", "March"),
+    ("apr", "April"),
+    ("may", "May"),
+    ("jun", "June"),
+    ("jul", "July"),
+    ("aug", "August"),
+    ("sep", "September"),
+    ("oct", "October"),
+    ("nov", "November"),
+    ("dec", "December"),
+];
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    strings: HashMap<String, String>,
+}
+
+impl Parser {
+    fn new(src: &str) -> Self {
+        let strings = MONTHS
+            .iter()
+            .map(|(abbr, month)| (abbr.to_string(), month.to_string()))
+            .collect();
+
+        Self {
+            chars: src.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+            strings,
+        }
+    }
+
+    fn parse(mut self) -> Result<Bibliography, ParseError> {
+        let mut bib = Bibliography::new();
+        while self.skip_to_entry() {
+            let (line, column) = (self.line, self.column);
+            self.bump();
+            self.skip_whitespace();
+
+            let kind = self.identifier()?;
+            self.skip_whitespace();
+            match kind.to_lowercase().as_str() {
+                "comment" => self.skip_comment(),
+                "preamble" => {
+                    let close = self.open()?;
+                    self.value()?;
+                    self.close(close)?;
+                }
+                "string" => {
+                    let close = self.open()?;
+                    let name = self.identifier()?;
+                    self.expect('=')?;
+                    let value = self.value()?;
+                    self.strings.insert(name.to_lowercase(), value);
+                    self.close(close)?;
+                }
+                _ => {
+                    let entry = self.entry(&kind)?;
+                    if bib.contains(entry.key()) {
+                        return Err(ParseError {
+                            line,
+                            column,
+                            message: format!("duplicate entry {}", entry.key()),
+                        });
+                    }
+                    bib.add(entry);
+                }
+            }
+        }
+
+        Ok(bib)
+    }
+
+    fn entry(&mut self, kind: &str) -> Result<Entry, ParseError> {
+        let close = self.open()?;
+        self.skip_whitespace();
+        let key = self.take_while(|c| !c.is_whitespace() && c != ',' && c != close);
+        if key.is_empty() {
+            return Err(self.error("expected an entry key"));
+        }
+        let mut entry = Entry::new(EntryType::from_name(kind), key);
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                    self.skip_whitespace();
+                    if self.peek() == Some(close) {
+                        continue;
+                    }
+
+                    let name = self.identifier()?;
+                    self.expect('=')?;
+                    let value = self.value()?;
+                    entry = entry.field(Field::from_name(name), value);
+                }
+                Some(c) if c == close => {
+                    self.bump();
+                    return Ok(entry);
+                }
+                Some(c) => return Err(self.error(format!("unexpected {:?} in entry", c))),
+                None => return Err(self.error("unterminated entry")),
+            }
+        }
+    }
+
+    /// A value with its parts concatenated by `#`.
+    fn value(&mut self) -> Result<String, ParseError> {
+        let mut buf = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => {
+                    self.bump();
+                    buf.push_str(&self.balanced('}')?);
+                }
+                Some('"') => {
+                    self.bump();
+                    buf.push_str(&self.balanced('"')?);
+                }
+                Some(c) if c.is_ascii_digit() => {
+                    buf.push_str(&self.take_while(|c| c.is_ascii_digit()));
+                }
+                Some(_) => {
+                    let (line, column) = (self.line, self.column);
+                    let name = self.identifier()?;
+                    match self.strings.get(&name.to_lowercase()) {
+                        Some(value) => buf.push_str(value),
+                        None => {
+                            return Err(ParseError {
+                                line,
+                                column,
+                                message: format!("undefined string {}", name),
+                            })
+                        }
+                    }
+                }
+                None => return Err(self.error("expected a value")),
+            }
+
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.bump();
+            } else {
+                return Ok(buf);
+            }
+        }
+    }
+
+    /// Reads until the terminator which isn't inside braces,
+    /// the opening delimiter is already consumed.
+    fn balanced(&mut self, terminator: char) -> Result<String, ParseError> {
+        // point at the opening delimiter
+        let (line, column) = (self.line, self.column - 1);
+        let mut depth = 0;
+        let mut buf = String::new();
+        while let Some(c) = self.bump() {
+            match c {
+                c if c == terminator && depth == 0 => return Ok(buf),
+                '{' => depth += 1,
+                '}' if depth == 0 => return Err(self.error("unbalanced '}'")),
+                '}' => depth -= 1,
+                _ => {}
+            }
+            buf.push(c);
+        }
+
+        Err(ParseError {
+            line,
+            column,
+            message: format!("missing closing {:?}", terminator),
+        })
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('{') {
+            self.bump();
+            // an unbalanced comment runs to the end of the file
+            let _ = self.balanced('}');
+        } else {
+            self.take_while(|c| c != '\n');
+        }
+    }
+
+    /// Skips text between entries, which BibTeX ignores.
+    fn skip_to_entry(&mut self) -> bool {
+        self.take_while(|c| c != '@');
+        self.peek().is_some()
+    }
+
+    fn open(&mut self) -> Result<char, ParseError> {
+        match self.peek() {
+            Some('{') => {
+                self.bump();
+                Ok('}')
+            }
+            Some('(') => {
+                self.bump();
+                Ok(')')
+            }
+            _ => Err(self.error("expected '{' or '('")),
+        }
+    }
+
+    fn close(&mut self, close: char) -> Result<(), ParseError> {
+        self.expect(close)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("expected {:?}, found {:?}", expected, c))),
+            None => Err(self.error(format!("expected {:?}, found end of file", expected))),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, ParseError> {
+        self.skip_whitespace();
+        let ident = self.take_while(|c| {
+            !c.is_whitespace() && !matches!(c, '{' | '}' | '(' | ')' | ',' | '=' | '#' | '"' | '@')
+        });
+        if ident.is_empty() {
+            Err(self.error("expected an identifier"))
+        } else {
+            Ok(ident)
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> String {
+        let mut buf = String::new();
+        while let Some(c) = self.peek() {
+            if !f(c) {
+                break;
+            }
+            buf.push(c);
+            self.bump();
+        }
+
+        buf
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn error<S: Into<String>>(&self, message: S) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        let bib = parse(
+            r#"Anything outside of entries is a comment.
+
+@Comment{ @article{ignored, title = {No}} }
+@STRING( tug = {TeX Users Group} )
+@preamble{ "\newcommand{\noop}[1]{}" }
+
+@Book{knuth:tex,
+  author    = "Donald E. Knuth",
+  title     = {The {\TeX}book},
+  publisher = tug # ", " # {Addison-Wesley},
+  month     = jun,
+  year      = 1984,
+}
+
+@misc(web, note = "a {"quoted"} value")
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(2, bib.len());
+
+        let book = bib.get("knuth:tex").unwrap();
+        assert_eq!(&EntryType::Book, book.kind());
+        assert_eq!(Some("Donald E. Knuth"), book.get(&Field::Author));
+        assert_eq!(Some("The {\\TeX}book"), book.get(&Field::Title));
+        assert_eq!(
+            Some("TeX Users Group, Addison-Wesley"),
+            book.get(&Field::Publisher)
+        );
+        assert_eq!(Some("June"), book.get(&Field::Month));
+        assert_eq!(Some("1984"), book.get(&Field::Year));
+
+        let web = bib.get("web").unwrap();
+        assert_eq!(Some("a {\"quoted\"} value"), web.get(&Field::Note));
+    }
+
+    #[test]
+    fn round_trip() {
+        let bib = parse("@article{a, title = {T}, year = 2020}").unwrap();
+        assert_eq!(bib, parse(&bib.to_bibtex()).unwrap());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Err(ParseError {
+                line: 2,
+                column: 22,
+                message: "undefined string acm".to_owned(),
+            }),
+            parse("@article{a,\n  title = {T}, org = acm}")
+        );
+        assert_eq!(
+            Err(ParseError {
+                line: 1,
+                column: 19,
+                message: "missing closing '}'".to_owned(),
+            }),
+            parse("@article{a, title={T").map(|_| ())
+        );
+        assert_eq!(
+            Err(ParseError {
+                line: 2,
+                column: 1,
+                message: "duplicate entry a".to_owned(),
+            }),
+            parse("@misc{a}\n@misc{a}").map(|_| ())
+        );
+    }
+}
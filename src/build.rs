@@ -0,0 +1,484 @@
+//! Compiling documents with a TeX engine.
+//!
+//! ```no_run
+//! use trylatex::{build::{Build, Engine}, Document, Text, Container};
+//!
+//! let doc = Document::new().with(Text("Hello"));
+//! let output = Build::new(Engine::XeLatex).run(&doc).unwrap();
+//! println!("{}", output.pdf.display());
+//! ```
+
+use crate::{
+    log::{Diagnostic, Kind, Report},
+    Backend, BibSetup, BuildError, Document, IoWriter,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    PdfLatex,
+    XeLatex,
+    LuaLatex,
+    /// Runs bibliography tools and reruns itself, so it's run only once.
+    Tectonic,
+}
+
+impl Engine {
+    /// The name of the binary looked up in `PATH`.
+    pub fn program(&self) -> &'static str {
+        match self {
+            Engine::PdfLatex => "pdflatex",
+            Engine::XeLatex => "xelatex",
+            Engine::LuaLatex => "lualatex",
+            Engine::Tectonic => "tectonic",
+        }
+    }
+
+    fn args(&self, file: &str) -> Vec<String> {
+        match self {
+            Engine::Tectonic => vec![
+                "--keep-logs".to_owned(),
+                "--keep-intermediates".to_owned(),
+                file.to_owned(),
+            ],
+            _ => vec![
+                "-interaction=nonstopmode".to_owned(),
+                "-halt-on-error".to_owned(),
+                "-file-line-error".to_owned(),
+                file.to_owned(),
+            ],
+        }
+    }
+}
+
+/// A compiled document.
+///
+/// A temporary directory is removed with the output,
+/// see [`Build::dir`] to keep the files.
+#[derive(Debug)]
+pub struct Output {
+    pub pdf: PathBuf,
+    /// The directory with the sources and the auxiliary files.
+    pub dir: PathBuf,
+    /// How many times the engine was run.
+    pub runs: usize,
+    /// The log of the last run.
+    pub report: Report,
+    /// Removes a temporary directory when the output is dropped.
+    _temp: Option<TempDir>,
+}
+
+/// Builds documents with a configured engine.
+pub struct Build {
+    engine: Engine,
+    program: Option<PathBuf>,
+    bibtex: PathBuf,
+    biber: PathBuf,
+    makeindex: PathBuf,
+    dir: Option<PathBuf>,
+    jobname: String,
+    max_runs: usize,
+}
+
+impl Build {
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            engine,
+            program: None,
+            bibtex: PathBuf::from("bibtex"),
+            biber: PathBuf::from("biber"),
+            makeindex: PathBuf::from("makeindex"),
+            dir: None,
+            jobname: "document".to_owned(),
+            max_runs: 5,
+        }
+    }
+
+    /// Sets the path to the engine binary instead of looking it up in `PATH`.
+    pub fn program<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.program = Some(path.into());
+        self
+    }
+
+    pub fn bibtex<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.bibtex = path.into();
+        self
+    }
+
+    pub fn biber<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.biber = path.into();
+        self
+    }
+
+    pub fn makeindex<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.makeindex = path.into();
+        self
+    }
+
+    /// Builds in the directory instead of a new temporary one.
+    pub fn dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// The name of the `.tex` file and so of the `.pdf`, `document` by default.
+    pub fn jobname<S: AsRef<str>>(mut self, jobname: S) -> Self {
+        self.jobname = jobname.as_ref().to_owned();
+        self
+    }
+
+    /// Limits how many times the engine is run to settle cross-references,
+    /// including the run after bibliography and index tools.
+    pub fn max_runs(mut self, runs: usize) -> Self {
+        self.max_runs = runs.max(1);
+        self
+    }
+
+    pub fn run(&self, doc: &Document) -> Result<Output, BuildError> {
+        let packages = doc.check().map_err(BuildError::Render)?;
+
+        let (dir, temp) = match &self.dir {
+            Some(dir) => {
+                fs::create_dir_all(dir)?;
+                (dir.clone(), None)
+            }
+            None => {
+                let temp = TempDir::new()?;
+                (temp.0.clone(), Some(temp))
+            }
+        };
+
+        let file = format!("{}.tex", self.jobname);
+        let mut tex = IoWriter::new(io::BufWriter::new(fs::File::create(dir.join(&file))?));
+        let result = doc.render_with(&packages, &mut tex);
+        tex.finish(result)?;
+
+        let bibliography = doc.preambule.bibliography.as_ref();
+        if let Some(setup) = bibliography {
+            if let Some(entries) = setup.get_entries() {
+                entries.write_to(dir.join(format!("{}.bib", setup.resource())))?;
+            }
+        }
+
+        let mut runs = 0;
+        let mut log = self.engine(&dir, &file, &mut runs)?;
+
+        if self.engine != Engine::Tectonic {
+            // the citations the bibliography tool was last run for
+            let mut cited = None;
+            let mut tools = self.bibliography(bibliography, &dir, &mut cited)?;
+            let index = format!("{}.idx", self.jobname);
+            if dir.join(&index).exists() {
+                tool(&self.makeindex, &dir, &[index])?;
+                tools = true;
+            }
+
+            if tools && runs < self.max_runs {
+                log = self.engine(&dir, &file, &mut runs)?;
+            }
+            while runs < self.max_runs {
+                let changed = self.bibliography(bibliography, &dir, &mut cited)?;
+                if !changed && !log.needs_rerun() {
+                    break;
+                }
+                log = self.engine(&dir, &file, &mut runs)?;
+            }
+        }
+
+        Ok(Output {
+            pdf: dir.join(format!("{}.pdf", self.jobname)),
+            dir,
+            runs,
+            report: log,
+            _temp: temp,
+        })
+    }
+
+    /// Runs bibtex or biber if the citations changed since it was last run,
+    /// returns whether it was run.
+    fn bibliography(
+        &self,
+        setup: Option<&BibSetup>,
+        dir: &Path,
+        cited: &mut Option<String>,
+    ) -> Result<bool, BuildError> {
+        let (program, citations) = match setup.map(BibSetup::backend) {
+            Some(Backend::Natbib) => {
+                let aux = fs::read_to_string(dir.join(format!("{}.aux", self.jobname)))
+                    .unwrap_or_default();
+                let citations = aux
+                    .lines()
+                    .filter(|line| {
+                        ["\\citation", "\\bibdata", "\\bibstyle"]
+                            .iter()
+                            .any(|command| line.starts_with(command))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if !citations.contains("\\citation") {
+                    return Ok(false);
+                }
+                (&self.bibtex, citations)
+            }
+            Some(Backend::Biblatex) => {
+                match fs::read_to_string(dir.join(format!("{}.bcf", self.jobname))) {
+                    Ok(bcf) => (&self.biber, bcf),
+                    Err(_) => return Ok(false),
+                }
+            }
+            None => return Ok(false),
+        };
+        if cited.as_ref() == Some(&citations) {
+            return Ok(false);
+        }
+
+        tool(program, dir, std::slice::from_ref(&self.jobname))?;
+        *cited = Some(citations);
+        Ok(true)
+    }
+
+    /// Runs the engine and returns what its log reports.
+    fn engine(&self, dir: &Path, file: &str, runs: &mut usize) -> Result<Report, BuildError> {
+        let program = match &self.program {
+            Some(program) => program.clone(),
+            None => PathBuf::from(self.engine.program()),
+        };
+
+        let status = Command::new(&program)
+            .args(self.engine.args(file))
+            .current_dir(dir)
+            .output()?
+            .status;
+        *runs += 1;
+
+        // logs aren't always valid UTF-8, e.g. with a legacy input encoding
+        let log = fs::read(dir.join(format!("{}.log", self.jobname)))
+            .map(|log| String::from_utf8_lossy(&log).into_owned())
+            .unwrap_or_default();
+
+        if !status.success() {
+            return Err(BuildError::Failed {
+                program: program.display().to_string(),
+                report: Report::parse(&log),
+            });
+        }
+
+        Ok(Report::parse(&log))
+    }
+}
+
+fn tool(program: &Path, dir: &Path, args: &[String]) -> Result<(), BuildError> {
+    let output = Command::new(program).args(args).current_dir(dir).output()?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stdout).into_owned();
+        return Err(BuildError::Failed {
+            program: program.display().to_string(),
+            report: Report {
+                diagnostics: vec![Diagnostic {
+                    kind: Kind::Error,
+                    message: message.trim().to_owned(),
+                    file: None,
+                    line: None,
+                }],
+            },
+        });
+    }
+
+    Ok(())
+}
+
+/// A temporary directory removed when it's dropped.
+#[derive(Debug)]
+struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates a new directory only the user can access,
+    /// one which already exists may belong to someone else.
+    fn new() -> io::Result<Self> {
+        loop {
+            let path = temp_dir();
+            let mut builder = fs::DirBuilder::new();
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+
+            match builder.create(&path) {
+                Ok(()) => return Ok(Self(path)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        // there's nothing to do about a directory which can't be removed
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn temp_dir() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+
+    std::env::temp_dir().join(format!(
+        "trylatex-{}-{}-{}",
+        std::process::id(),
+        nanos,
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{Cite, Container, Ref, Section};
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Writes a script which pretends to be an engine.
+    fn stub(dir: &Path, name: &str, script: &str) -> PathBuf {
+        fs::create_dir_all(dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}", script)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn reruns_until_references_settle() {
+        let bin = TempDir::new().unwrap();
+        // the first two runs ask for a rerun
+        let engine = stub(
+            &bin.0,
+            "engine",
+            r#"echo run >> runs
+if [ $(wc -l < runs) -lt 3 ]; then
+  echo "LaTeX Warning: Label(s) may have changed. Rerun to get cross-references right." > document.log
+else
+  echo "LaTeX Warning: Reference \`x' on page 1 undefined on input line 7." > document.log
+fi
+touch document.pdf
+"#,
+        );
+
+        let (section, label) = Section::new("A").label("sec:a");
+        let doc = Document::new().with(section).with(Ref::new(&label));
+        let output = Build::new(Engine::PdfLatex)
+            .program(&engine)
+            .run(&doc)
+            .unwrap();
+
+        assert_eq!(3, output.runs);
+        assert!(output.pdf.exists());
+        assert!(fs::read_to_string(output.dir.join("document.tex"))
+            .unwrap()
+            .contains("\\ref{sec:a}"));
+        assert_eq!(
+            vec![Diagnostic {
+                kind: Kind::UndefinedReference("x".to_owned()),
+                message: "Reference `x' on page 1 undefined on input line 7.".to_owned(),
+                file: None,
+                line: Some(7),
+            }],
+            output.report.diagnostics
+        );
+        let dir = output.dir.clone();
+        drop(output);
+        assert!(!dir.exists());
+
+        let output = Build::new(Engine::PdfLatex)
+            .program(&engine)
+            .max_runs(1)
+            .run(&doc)
+            .unwrap();
+        assert_eq!(1, output.runs);
+    }
+
+    #[test]
+    fn reruns_bibliography_when_citations_change() {
+        let bin = TempDir::new().unwrap();
+        // the citation of `b` shows up in the aux file after the first run
+        let engine = stub(
+            &bin.0,
+            "engine",
+            r#"echo run >> runs
+if [ $(wc -l < runs) -lt 2 ]; then
+  printf '\\citation{a}\n\\bibdata{refs}\n' > document.aux
+else
+  printf '\\citation{a}\n\\citation{b}\n\\bibdata{refs}\n' > document.aux
+fi
+: > document.log
+touch document.pdf
+"#,
+        );
+        let bibtex = stub(&bin.0, "bibtex", "echo run >> bibtex-runs\n");
+
+        let mut doc = Document::new();
+        doc.preambule()
+            .bibliography(crate::BibSetup::natbib("refs"))
+            .unwrap();
+        let doc = doc.with(Cite::citep("a")).with(Cite::citep("b"));
+        let output = Build::new(Engine::PdfLatex)
+            .program(&engine)
+            .bibtex(&bibtex)
+            .run(&doc)
+            .unwrap();
+
+        assert_eq!(3, output.runs);
+        assert_eq!(
+            2,
+            fs::read_to_string(output.dir.join("bibtex-runs"))
+                .unwrap()
+                .lines()
+                .count()
+        );
+        assert_eq!(
+            0o700,
+            fs::metadata(&output.dir).unwrap().permissions().mode() & 0o777
+        );
+
+        let output = Build::new(Engine::PdfLatex)
+            .program(&engine)
+            .bibtex(&bibtex)
+            .max_runs(1)
+            .run(&doc)
+            .unwrap();
+        assert_eq!(1, output.runs);
+    }
+
+    #[test]
+    fn failure_has_report() {
+        let bin = TempDir::new().unwrap();
+        let engine = stub(
+            &bin.0,
+            "engine",
+            "printf '! Undefined control sequence.\\nl.5 \\\\foo\\n' > document.log\nexit 1\n",
+        );
+
+        match Build::new(Engine::XeLatex)
+            .program(&engine)
+            .run(&Document::new())
+        {
+            Err(BuildError::Failed { report, .. }) => assert_eq!(
+                vec![Diagnostic {
+                    kind: Kind::Error,
+                    message: "Undefined control sequence.".to_owned(),
+                    file: None,
+                    line: Some(5),
+                }],
+                report.diagnostics
+            ),
+            other => panic!("unexpected {:?}", other.map(|o| o.runs)),
+        }
+    }
+}
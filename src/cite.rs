@@ -0,0 +1,438 @@
+use std::fmt;
+
+use crate::bib::Bibliography;
+use crate::node::Node;
+use crate::{Context, Element, Error, Macros, Package, Parameter, Raw};
+
+/// How the bibliography is processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Backend {
+    /// `natbib` with `bibtex`.
+    Natbib,
+    /// `biblatex` with `biber`.
+    Biblatex,
+}
+
+/// The bibliography of a document, set by [`Preambule::bibliography`](crate::Preambule::bibliography).
+///
+/// ```
+/// use trylatex::bib::{Bibliography, Entry, EntryType, Field};
+/// use trylatex::{BibSetup, Cite, Container, Document};
+///
+/// let mut bib = Bibliography::new();
+/// bib.add(Entry::new(EntryType::Book, "knuth").field(Field::Title, "The TeXbook"));
+///
+/// let mut doc = Document::new();
+/// doc.preambule()
+///     .bibliography(BibSetup::biblatex("refs").entries(bib))
+///     .unwrap();
+/// let doc = doc.with(Cite::textcite("knuth"));
+///
+/// assert!(doc.try_render().unwrap().ends_with("\\printbibliography\n\\end{document}\n"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BibSetup {
+    backend: Backend,
+    resource: String,
+    style: String,
+    entries: Option<Bibliography>,
+}
+
+impl BibSetup {
+    /// Uses `natbib`, the entries are expected in `{resource}.bib`.
+    pub fn natbib<S: AsRef<str>>(resource: S) -> Self {
+        Self {
+            backend: Backend::Natbib,
+            resource: resource.as_ref().to_owned(),
+            style: "plainnat".to_owned(),
+            entries: None,
+        }
+    }
+
+    /// Uses `biblatex`, the entries are expected in `{resource}.bib`.
+    pub fn biblatex<S: AsRef<str>>(resource: S) -> Self {
+        Self {
+            backend: Backend::Biblatex,
+            resource: resource.as_ref().to_owned(),
+            style: "numeric".to_owned(),
+            entries: None,
+        }
+    }
+
+    /// Sets the style, `\bibliographystyle` of natbib or `style` of biblatex.
+    pub fn style<S: AsRef<str>>(mut self, style: S) -> Self {
+        self.style = style.as_ref().to_owned();
+        self
+    }
+
+    /// Sets the entries citations are checked against.
+    pub fn entries(mut self, entries: Bibliography) -> Self {
+        self.entries = Some(entries);
+        self
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// The name of the `.bib` file without an extension.
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+
+    pub fn get_entries(&self) -> Option<&Bibliography> {
+        self.entries.as_ref()
+    }
+
+    pub(crate) fn package(&self) -> Package {
+        match self.backend {
+            Backend::Natbib => Package::new("natbib"),
+            Backend::Biblatex => Package::new("biblatex")
+                .value("backend", "biber")
+                .value("style", &self.style),
+        }
+    }
+
+    /// What goes into the preambule after packages.
+    pub(crate) fn render_preambule(&self) -> Option<String> {
+        match self.backend {
+            Backend::Natbib => None,
+            Backend::Biblatex => Some(
+                Macros::new("addbibresource")
+                    .param(Raw(format!("{}.bib", self.resource)))
+                    .render(),
+            ),
+        }
+    }
+
+    /// What goes at the end of the document.
+    pub(crate) fn render_end(&self) -> String {
+        match self.backend {
+            Backend::Natbib => format!(
+                "{}\n{}",
+                Macros::new("bibliographystyle")
+                    .param(Raw(&self.style))
+                    .render(),
+                Macros::new("bibliography")
+                    .param(Raw(&self.resource))
+                    .render()
+            ),
+            Backend::Biblatex => Macros::new("printbibliography").render(),
+        }
+    }
+
+    /// Checks citations are supported by the backend and cite known entries.
+    pub(crate) fn check(&self, citations: &[(CiteCommand, String)]) -> Result<(), Error> {
+        for (command, key) in citations {
+            if !command.supported_by(self.backend) {
+                return Err(Error::UnsupportedCitation {
+                    command: format!("\\{}", command),
+                    package: self.package().name().to_owned(),
+                });
+            }
+
+            if let Some(entries) = &self.entries {
+                if !entries.contains(key) {
+                    return Err(Error::UndefinedCitation(key.clone()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Citations of a document compared to the entries of its bibliography.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CitationReport {
+    /// Cited keys which aren't in the bibliography.
+    pub missing: Vec<String>,
+    /// Keys of entries which aren't cited.
+    pub unused: Vec<String>,
+}
+
+impl CitationReport {
+    pub(crate) fn new(entries: Option<&Bibliography>, citations: &[(CiteCommand, String)]) -> Self {
+        let mut report = Self::default();
+        for (_, key) in citations {
+            let known = entries.is_some_and(|e| e.contains(key));
+            if !known && !report.missing.contains(key) {
+                report.missing.push(key.clone());
+            }
+        }
+
+        if let Some(entries) = entries {
+            report.unused = entries
+                .iter()
+                .map(|e| e.key())
+                .filter(|key| citations.iter().all(|(_, cited)| cited != key))
+                .map(|key| key.to_owned())
+                .collect();
+        }
+
+        report
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.unused.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CiteCommand {
+    Cite,
+    /// `\citep` of natbib.
+    Citep,
+    /// `\citet` of natbib.
+    Citet,
+    /// `\parencite` of biblatex.
+    Parencite,
+    /// `\textcite` of biblatex.
+    Textcite,
+}
+
+impl CiteCommand {
+    fn supported_by(&self, backend: Backend) -> bool {
+        match self {
+            CiteCommand::Cite => true,
+            CiteCommand::Citep | CiteCommand::Citet => backend == Backend::Natbib,
+            CiteCommand::Parencite | CiteCommand::Textcite => backend == Backend::Biblatex,
+        }
+    }
+}
+
+impl fmt::Display for CiteCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CiteCommand::Cite => f.write_str("cite"),
+            CiteCommand::Citep => f.write_str("citep"),
+            CiteCommand::Citet => f.write_str("citet"),
+            CiteCommand::Parencite => f.write_str("parencite"),
+            CiteCommand::Textcite => f.write_str("textcite"),
+        }
+    }
+}
+
+/// A citation of one or more bibliography entries.
+pub struct Cite {
+    command: CiteCommand,
+    keys: Vec<String>,
+    postnote: Option<Parameter>,
+}
+
+impl Cite {
+    pub fn new<S: AsRef<str>>(command: CiteCommand, key: S) -> Self {
+        Self {
+            command,
+            keys: vec![key.as_ref().to_owned()],
+            postnote: None,
+        }
+    }
+
+    #[allow(clippy::self_named_constructors)]
+    pub fn cite<S: AsRef<str>>(key: S) -> Self {
+        Self::new(CiteCommand::Cite, key)
+    }
+
+    pub fn citep<S: AsRef<str>>(key: S) -> Self {
+        Self::new(CiteCommand::Citep, key)
+    }
+
+    pub fn citet<S: AsRef<str>>(key: S) -> Self {
+        Self::new(CiteCommand::Citet, key)
+    }
+
+    pub fn parencite<S: AsRef<str>>(key: S) -> Self {
+        Self::new(CiteCommand::Parencite, key)
+    }
+
+    pub fn textcite<S: AsRef<str>>(key: S) -> Self {
+        Self::new(CiteCommand::Textcite, key)
+    }
+
+    /// Cites one more entry.
+    pub fn key<S: AsRef<str>>(mut self, key: S) -> Self {
+        self.keys.push(key.as_ref().to_owned());
+        self
+    }
+
+    /// Sets a note after the citation, e.g. the page.
+    pub fn note<P: Into<Parameter>>(mut self, note: P) -> Self {
+        self.postnote = Some(note.into());
+        self
+    }
+}
+
+impl Element for Cite {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut m = Macros::new(self.command.to_string());
+        if let Some(note) = &self.postnote {
+            m = m.opt(note.clone());
+        }
+
+        m.param(Raw(self.keys.join(","))).render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        for key in &self.keys {
+            ctx.cite(self.command, key);
+        }
+
+        Ok(())
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Cite {
+            command: self.command,
+            keys: self.keys.clone(),
+            note: self.postnote.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bib::{Entry, EntryType};
+    use crate::{Container, Document, Text};
+
+    fn bib() -> Bibliography {
+        let mut bib = Bibliography::new();
+        bib.add(Entry::new(EntryType::Book, "a"))
+            .add(Entry::new(EntryType::Book, "b"));
+        bib
+    }
+
+    #[test]
+    fn natbib() {
+        let mut doc = Document::new();
+        doc.preambule()
+            .bibliography(BibSetup::natbib("refs").entries(bib()))
+            .unwrap();
+        let doc = doc
+            .with(Text("As shown by "))
+            .with(Cite::citet("a"))
+            .with(Cite::citep("a").key("b").note(Raw("p.~3")));
+
+        assert_eq!(
+            "\\documentclass{article}
+\\usepackage{natbib}
+
+\\begin{document}
+As shown by \\citet{a}\\citep[p.~3]{a,b}
+\\bibliographystyle{plainnat}
+\\bibliography{refs}
+\\end{document}
+",
+            doc.try_render().unwrap()
+        );
+    }
+
+    #[test]
+    fn biblatex() {
+        let mut doc = Document::new();
+        doc.preambule()
+            .bibliography(BibSetup::biblatex("refs").style("authoryear"))
+            .unwrap();
+        let doc = doc.with(Cite::parencite("anything"));
+
+        assert_eq!(
+            "\\documentclass{article}
+\\usepackage[backend=biber,style=authoryear]{biblatex}
+\\addbibresource{refs.bib}
+
+\\begin{document}
+\\parencite{anything}
+\\printbibliography
+\\end{document}
+",
+            doc.try_render().unwrap()
+        );
+    }
+
+    #[test]
+    fn replaced_setup() {
+        let mut doc = Document::new();
+        doc.preambule()
+            .use_package("amsmath")
+            .bibliography(BibSetup::natbib("refs"))
+            .unwrap()
+            .bibliography(BibSetup::biblatex("refs"))
+            .unwrap()
+            .bibliography(BibSetup::biblatex("refs").style("authoryear"))
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                Package::new("amsmath"),
+                Package::new("biblatex")
+                    .value("backend", "biber")
+                    .value("style", "authoryear"),
+            ],
+            doc.preambule()
+                .packages()
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn report() {
+        let mut doc = Document::new();
+        doc.preambule()
+            .bibliography(BibSetup::biblatex("refs").entries(bib()))
+            .unwrap();
+        let doc = doc
+            .with(Cite::textcite("a").key("c"))
+            .with(Cite::parencite("c"));
+
+        assert_eq!(
+            CitationReport {
+                missing: vec!["c".to_owned()],
+                unused: vec!["b".to_owned()],
+            },
+            doc.citation_report().unwrap()
+        );
+    }
+
+    #[test]
+    fn checks() {
+        let mut doc = Document::new();
+        doc.preambule()
+            .bibliography(BibSetup::natbib("refs").entries(bib()))
+            .unwrap();
+        let doc = doc.with(Cite::citep("c"));
+        assert_eq!(
+            Err(Error::UndefinedCitation("c".to_owned())),
+            doc.try_render()
+        );
+
+        let mut doc = Document::new();
+        doc.preambule()
+            .bibliography(BibSetup::natbib("refs"))
+            .unwrap();
+        let doc = doc.with(Cite::textcite("a"));
+        assert_eq!(
+            Err(Error::UnsupportedCitation {
+                command: "\\textcite".to_owned(),
+                package: "natbib".to_owned(),
+            }),
+            doc.try_render()
+        );
+
+        let doc = Document::new().with(Cite::cite("a"));
+        assert_eq!(Err(Error::MissingBibliography), doc.try_render());
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum DocumentType {
+    Article,
+    Report,
+    Book,
+    Letter,
+    Beamer,
+    Memoir,
+    /// KOMA-Script `scrartcl`
+    ScrArticle,
+    /// KOMA-Script `scrreprt`
+    ScrReport,
+    /// KOMA-Script `scrbook`
+    ScrBook,
+    Standalone,
+    /// Any other class, by its name, with what it provides
+    /// as there's no way to know.
+    Custom {
+        name: String,
+        /// Whether it provides `\chapter`.
+        chapters: bool,
+        /// Whether it's based on `beamer`.
+        frames: bool,
+    },
+}
+
+impl DocumentType {
+    /// Another class which provides neither chapters nor frames.
+    pub fn custom<S: AsRef<str>>(name: S) -> Self {
+        DocumentType::Custom {
+            name: name.as_ref().to_owned(),
+            chapters: false,
+            frames: false,
+        }
+    }
+
+    /// Whether the class provides `\chapter`.
+    pub fn has_chapters(&self) -> bool {
+        match self {
+            DocumentType::Report
+            | DocumentType::Book
+            | DocumentType::Memoir
+            | DocumentType::ScrReport
+            | DocumentType::ScrBook => true,
+            DocumentType::Custom { chapters, .. } => *chapters,
+            DocumentType::Article
+            | DocumentType::Letter
+            | DocumentType::Beamer
+            | DocumentType::ScrArticle
+            | DocumentType::Standalone => false,
+        }
+    }
+
+    /// Whether the class makes presentations of frames and overlays.
+    pub fn has_frames(&self) -> bool {
+        match self {
+            DocumentType::Beamer => true,
+            DocumentType::Custom { frames, .. } => *frames,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for DocumentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocumentType::Article => f.write_str("article"),
+            DocumentType::Report => f.write_str("report"),
+            DocumentType::Book => f.write_str("book"),
+            DocumentType::Letter => f.write_str("letter"),
+            DocumentType::Beamer => f.write_str("beamer"),
+            DocumentType::Memoir => f.write_str("memoir"),
+            DocumentType::ScrArticle => f.write_str("scrartcl"),
+            DocumentType::ScrReport => f.write_str("scrreprt"),
+            DocumentType::ScrBook => f.write_str("scrbook"),
+            DocumentType::Standalone => f.write_str("standalone"),
+            DocumentType::Custom { name, .. } => f.write_str(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum PaperSize {
+    A4,
+    A5,
+    B5,
+    Letter,
+    Legal,
+    Executive,
+}
+
+impl fmt::Display for PaperSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaperSize::A4 => f.write_str("a4paper"),
+            PaperSize::A5 => f.write_str("a5paper"),
+            PaperSize::B5 => f.write_str("b5paper"),
+            PaperSize::Letter => f.write_str("letterpaper"),
+            PaperSize::Legal => f.write_str("legalpaper"),
+            PaperSize::Executive => f.write_str("executivepaper"),
+        }
+    }
+}
+
+/// Base font size of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum FontSize {
+    Pt10,
+    Pt11,
+    Pt12,
+}
+
+impl fmt::Display for FontSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontSize::Pt10 => f.write_str("10pt"),
+            FontSize::Pt11 => f.write_str("11pt"),
+            FontSize::Pt12 => f.write_str("12pt"),
+        }
+    }
+}
+
+/// An option of `\documentclass`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ClassOption {
+    Paper(PaperSize),
+    FontSize(FontSize),
+    OneColumn,
+    TwoColumn,
+    OneSide,
+    TwoSide,
+    Draft,
+    Final,
+    /// Any other option, rendered verbatim.
+    Other(String),
+}
+
+impl ClassOption {
+    /// Whether the options set the same thing,
+    /// so only one of them can be in effect.
+    pub(crate) fn overrides(&self, other: &ClassOption) -> bool {
+        use ClassOption::*;
+
+        match (self, other) {
+            (Paper(_), Paper(_)) | (FontSize(_), FontSize(_)) => true,
+            (OneColumn, OneColumn) | (OneColumn, TwoColumn) => true,
+            (TwoColumn, OneColumn) | (TwoColumn, TwoColumn) => true,
+            (OneSide, OneSide) | (OneSide, TwoSide) => true,
+            (TwoSide, OneSide) | (TwoSide, TwoSide) => true,
+            (Draft, Draft) | (Draft, Final) | (Final, Draft) | (Final, Final) => true,
+            (Other(a), Other(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ClassOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClassOption::Paper(paper) => paper.fmt(f),
+            ClassOption::FontSize(size) => size.fmt(f),
+            ClassOption::OneColumn => f.write_str("onecolumn"),
+            ClassOption::TwoColumn => f.write_str("twocolumn"),
+            ClassOption::OneSide => f.write_str("oneside"),
+            ClassOption::TwoSide => f.write_str("twoside"),
+            ClassOption::Draft => f.write_str("draft"),
+            ClassOption::Final => f.write_str("final"),
+            ClassOption::Other(option) => f.write_str(option),
+        }
+    }
+}
+
+impl From<PaperSize> for ClassOption {
+    fn from(paper: PaperSize) -> Self {
+        ClassOption::Paper(paper)
+    }
+}
+
+impl From<FontSize> for ClassOption {
+    fn from(size: FontSize) -> Self {
+        ClassOption::FontSize(size)
+    }
+}
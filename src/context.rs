@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{CiteCommand, DocumentType, Error, Label, Package, Packages, Preambule};
+
+/// What is known about the document while its elements are visited.
+pub struct Context {
+    class: DocumentType,
+    packages: Packages,
+    depth: HashMap<&'static str, usize>,
+    labels: Vec<Label>,
+    references: Vec<Label>,
+    citations: Vec<(CiteCommand, String)>,
+}
+
+impl Context {
+    pub fn new(preambule: &Preambule) -> Self {
+        Self {
+            class: preambule.r#type.clone(),
+            packages: Packages::new(),
+            depth: HashMap::new(),
+            labels: Vec::new(),
+            references: Vec::new(),
+            citations: Vec::new(),
+        }
+    }
+
+    /// The class of the document being visited.
+    pub fn class(&self) -> &DocumentType {
+        &self.class
+    }
+
+    /// Adds a package to the preambule when the document is rendered.
+    pub fn require(&mut self, package: Package) -> Result<(), Error> {
+        self.packages.add(package)
+    }
+
+    /// Packages required by the visited elements.
+    pub fn packages(&self) -> &Packages {
+        &self.packages
+    }
+
+    /// Marks the start of a nested structure and returns how deep it's nested,
+    /// starting from 1.
+    pub fn enter(&mut self, kind: &'static str) -> usize {
+        let depth = self.depth.entry(kind).or_insert(0);
+        *depth += 1;
+        *depth
+    }
+
+    /// Marks the end of a structure started by [`Context::enter`].
+    pub fn leave(&mut self, kind: &'static str) {
+        if let Some(depth) = self.depth.get_mut(kind) {
+            *depth = depth.saturating_sub(1);
+        }
+    }
+
+    /// Records a label defined by an element.
+    pub fn define(&mut self, label: &Label) {
+        self.labels.push(label.clone());
+    }
+
+    /// Records a reference to a label.
+    pub fn reference(&mut self, label: &Label) {
+        self.references.push(label.clone());
+    }
+
+    /// Records a citation of a bibliography entry.
+    pub fn cite<S: AsRef<str>>(&mut self, command: CiteCommand, key: S) {
+        self.citations.push((command, key.as_ref().to_owned()));
+    }
+
+    /// Citations made by the visited elements.
+    pub fn citations(&self) -> &[(CiteCommand, String)] {
+        &self.citations
+    }
+
+    /// Checks every label is defined once and every reference
+    /// points to a defined label.
+    pub(crate) fn check_labels(&self) -> Result<(), Error> {
+        let mut defined = HashSet::new();
+        for label in &self.labels {
+            if !defined.insert(label) {
+                return Err(Error::DuplicateLabel(label.key().to_owned()));
+            }
+        }
+
+        match self.references.iter().find(|r| !defined.contains(r)) {
+            Some(label) => Err(Error::UndefinedLabel(label.key().to_owned())),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use std::fmt;
+
+use crate::environment::check_name;
+use crate::{Element, Environment, Error, Macros, Parameter, Raw};
+
+/// LaTeX allows up to 9 arguments.
+const MAX_ARGS: usize = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    New,
+    Renew,
+    Provide,
+}
+
+/// A definition of a command, `\newcommand` and alike.
+///
+/// The body is LaTeX code, `#1`..`#N` are the arguments.
+///
+/// ```
+/// use trylatex::{CommandDef, Container, Document, Element};
+///
+/// let mut doc = Document::new();
+/// let pair = doc
+///     .preambule()
+///     .command(CommandDef::<2>::new("pair", "(#1, #2)"))
+///     .unwrap();
+/// let doc = doc.with(pair.call(["a", "b"]));
+///
+/// assert!(doc.render().contains("\\newcommand{\\pair}[2]{(#1, #2)}"));
+/// assert!(doc.render().contains("\\pair{a}{b}"));
+/// ```
+///
+/// A call with another number of arguments doesn't compile:
+///
+/// ```compile_fail
+/// # use trylatex::{CommandDef, Document};
+/// let mut doc = Document::new();
+/// let pair = doc.preambule().command(CommandDef::<2>::new("pair", "(#1, #2)")).unwrap();
+/// pair.call(["a", "b", "c"]);
+/// ```
+pub struct CommandDef<const N: usize, const OPT: bool = false> {
+    kind: Kind,
+    name: String,
+    default: Option<Parameter>,
+    body: String,
+}
+
+impl<const N: usize> CommandDef<N> {
+    /// `\newcommand`, the command must not be defined.
+    pub fn new<S: AsRef<str>, B: AsRef<str>>(name: S, body: B) -> Self {
+        Self::with_kind(Kind::New, name, body)
+    }
+
+    /// `\renewcommand`, the command must be defined, e.g. by LaTeX.
+    pub fn renew<S: AsRef<str>, B: AsRef<str>>(name: S, body: B) -> Self {
+        Self::with_kind(Kind::Renew, name, body)
+    }
+
+    /// `\providecommand`, it does nothing if the command is defined.
+    pub fn provide<S: AsRef<str>, B: AsRef<str>>(name: S, body: B) -> Self {
+        Self::with_kind(Kind::Provide, name, body)
+    }
+
+    /// Adds an optional first argument with the default,
+    /// it goes before the `N` mandatory ones and is `#1` in the body.
+    pub fn default<P: Into<Parameter>>(self, default: P) -> CommandDef<N, true> {
+        CommandDef {
+            kind: self.kind,
+            name: self.name,
+            default: Some(default.into()),
+            body: self.body,
+        }
+    }
+
+    fn with_kind<S: AsRef<str>, B: AsRef<str>>(kind: Kind, name: S, body: B) -> Self {
+        Self {
+            kind,
+            name: name.as_ref().to_owned(),
+            default: None,
+            body: body.as_ref().to_owned(),
+        }
+    }
+}
+
+impl<const N: usize, const OPT: bool> CommandDef<N, OPT> {
+    const ARITY: usize = arity(N, OPT);
+
+    pub(crate) fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Checks the name and the body and returns the definition and the handle.
+    pub(crate) fn define(self) -> Result<(Macros, Command<N, OPT>), Error> {
+        // a control sequence is made of letters, `@` in packages
+        let legal = |c: char| c.is_ascii_alphabetic() || c == '@';
+        if self.name.is_empty() || !self.name.chars().all(legal) {
+            return Err(Error::IllegalCommandName(self.name));
+        }
+        check_body(&self.name, &self.body, Self::ARITY)?;
+
+        let command = match self.kind {
+            Kind::New => "newcommand",
+            Kind::Renew => "renewcommand",
+            Kind::Provide => "providecommand",
+        };
+        let m = Macros::new(command).param(Raw(format!("\\{}", self.name)));
+        let m = signature(m, Self::ARITY, self.default).param(Raw(self.body));
+
+        Ok((m, Command { name: self.name }))
+    }
+}
+
+/// A command defined in the preambule, it takes exactly `N` mandatory arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command<const N: usize, const OPT: bool = false> {
+    name: String,
+}
+
+impl<const N: usize, const OPT: bool> Command<N, OPT> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// A call with the default of the optional argument, if any.
+    pub fn call<P: Into<Parameter>>(&self, args: [P; N]) -> Macros {
+        IntoIterator::into_iter(args).fold(Macros::new(&self.name), Macros::param)
+    }
+}
+
+impl<const N: usize> Command<N, true> {
+    pub fn call_with<O, P>(&self, opt: O, args: [P; N]) -> Macros
+    where
+        O: Into<Parameter>,
+        P: Into<Parameter>,
+    {
+        IntoIterator::into_iter(args).fold(Macros::new(&self.name).opt(opt), Macros::param)
+    }
+}
+
+/// A command without arguments is used as is.
+impl Element for Command<0> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        Macros::new(&self.name).render_to(w)
+    }
+}
+
+/// A definition of an environment, `\newenvironment` or `\renewenvironment`.
+///
+/// The arguments are `#1`..`#N` in the code at the beginning,
+/// the code at the end can't use them.
+pub struct EnvironmentDef<const N: usize, const OPT: bool = false> {
+    renew: bool,
+    name: String,
+    default: Option<Parameter>,
+    begin: String,
+    end: String,
+}
+
+impl<const N: usize> EnvironmentDef<N> {
+    /// `\newenvironment`, the environment must not be defined.
+    pub fn new<S, B, E>(name: S, begin: B, end: E) -> Self
+    where
+        S: AsRef<str>,
+        B: AsRef<str>,
+        E: AsRef<str>,
+    {
+        Self::with_kind(false, name, begin, end)
+    }
+
+    /// `\renewenvironment`, the environment must be defined.
+    pub fn renew<S, B, E>(name: S, begin: B, end: E) -> Self
+    where
+        S: AsRef<str>,
+        B: AsRef<str>,
+        E: AsRef<str>,
+    {
+        Self::with_kind(true, name, begin, end)
+    }
+
+    /// Adds an optional first argument with the default,
+    /// it goes before the `N` mandatory ones and is `#1` in the body.
+    pub fn default<P: Into<Parameter>>(self, default: P) -> EnvironmentDef<N, true> {
+        EnvironmentDef {
+            renew: self.renew,
+            name: self.name,
+            default: Some(default.into()),
+            begin: self.begin,
+            end: self.end,
+        }
+    }
+
+    fn with_kind<S, B, E>(renew: bool, name: S, begin: B, end: E) -> Self
+    where
+        S: AsRef<str>,
+        B: AsRef<str>,
+        E: AsRef<str>,
+    {
+        Self {
+            renew,
+            name: name.as_ref().to_owned(),
+            default: None,
+            begin: begin.as_ref().to_owned(),
+            end: end.as_ref().to_owned(),
+        }
+    }
+}
+
+impl<const N: usize, const OPT: bool> EnvironmentDef<N, OPT> {
+    const ARITY: usize = arity(N, OPT);
+
+    pub(crate) fn kind(&self) -> Kind {
+        if self.renew {
+            Kind::Renew
+        } else {
+            Kind::New
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn define(self) -> Result<(Macros, UserEnvironment<N, OPT>), Error> {
+        check_name(&self.name)?;
+        check_body(&self.name, &self.begin, Self::ARITY)?;
+        check_body(&self.name, &self.end, 0)?;
+
+        let command = if self.renew {
+            "renewenvironment"
+        } else {
+            "newenvironment"
+        };
+        let m = Macros::new(command).param(Raw(&self.name));
+        let m = signature(m, Self::ARITY, self.default)
+            .param(Raw(self.begin))
+            .param(Raw(self.end));
+
+        Ok((m, UserEnvironment { name: self.name }))
+    }
+}
+
+/// An environment defined in the preambule, it takes exactly `N` mandatory arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserEnvironment<const N: usize, const OPT: bool = false> {
+    name: String,
+}
+
+impl<const N: usize, const OPT: bool> UserEnvironment<N, OPT> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Starts the environment with the default of the optional argument, if any.
+    pub fn begin<'a, P: Into<Parameter>>(&self, args: [P; N]) -> Environment<'a> {
+        IntoIterator::into_iter(args).fold(Environment::new(&self.name), Environment::param)
+    }
+}
+
+impl<const N: usize> UserEnvironment<N, true> {
+    pub fn begin_with<'a, O, P>(&self, opt: O, args: [P; N]) -> Environment<'a>
+    where
+        O: Into<Parameter>,
+        P: Into<Parameter>,
+    {
+        IntoIterator::into_iter(args)
+            .fold(Environment::new(&self.name).opt(opt), Environment::param)
+    }
+}
+
+/// The number of arguments in the body, checked when the definition is made.
+const fn arity(n: usize, opt: bool) -> usize {
+    let arity = n + opt as usize;
+    assert!(arity <= MAX_ARGS, "LaTeX allows up to 9 arguments");
+    arity
+}
+
+/// `[N][default]` of a definition.
+fn signature(mut m: Macros, arity: usize, default: Option<Parameter>) -> Macros {
+    if arity > 0 {
+        m = m.opt(Raw(arity.to_string()));
+    }
+    if let Some(default) = default {
+        m = m.opt(default);
+    }
+
+    m
+}
+
+/// Checks the body uses only the arguments which are declared,
+/// `##1` is an argument of a definition inside the body.
+fn check_body(name: &str, body: &str, arity: usize) -> Result<(), Error> {
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '#' => match chars.next() {
+                Some('#') => {
+                    chars.next();
+                }
+                Some(c) => match c.to_digit(10) {
+                    Some(number) if number >= 1 && number as usize <= arity => {}
+                    Some(number) => {
+                        return Err(Error::IllegalParameter {
+                            name: name.to_owned(),
+                            number: number as usize,
+                            arity,
+                        })
+                    }
+                    None => {}
+                },
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Container, Document, Text};
+
+    #[test]
+    fn render() {
+        let mut doc = Document::new();
+        let preambule = doc.preambule();
+        let r = preambule
+            .command(CommandDef::<0>::new("R", "\\mathbb{R}"))
+            .unwrap();
+        let greet = preambule
+            .command(CommandDef::<1>::renew("greet", "#1, #2!").default("Hello"))
+            .unwrap();
+        let note = preambule
+            .environment(EnvironmentDef::<1>::new("note", "\\textbf{#1}:", ""))
+            .unwrap();
+        preambule
+            .command(CommandDef::<0>::provide("R", "\\mathbf{R}"))
+            .unwrap();
+
+        let doc = doc
+            .with(r)
+            .with(greet.call(["world"]))
+            .with(greet.call_with("Hi", ["you"]))
+            .with(note.begin(["Note"]).with(Text("text")));
+
+        assert_eq!(
+            r"\documentclass{article}
+\newcommand{\R}{\mathbb{R}}
+\renewcommand{\greet}[2][Hello]{#1, #2!}
+\newenvironment{note}[1]{\textbf{#1}:}{}
+\providecommand{\R}{\mathbf{R}}
+
+\begin{document}
+\R\greet{world}\greet[Hi]{you}\begin{note}{Note}
+text
+\end{note}
+\end{document}
+",
+            doc.try_render().unwrap()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut doc = Document::new();
+        let preambule = doc.preambule();
+        preambule
+            .environment(EnvironmentDef::<0>::new("note", "", ""))
+            .unwrap();
+
+        assert_eq!(
+            Err(Error::DuplicateDefinition("endnote".to_owned())),
+            preambule
+                .command(CommandDef::<0>::new("endnote", ""))
+                .map(|c| c.name().to_owned())
+        );
+        // the handle would have other arguments than the present command
+        assert_eq!(
+            Err(Error::DuplicateDefinition("note".to_owned())),
+            preambule
+                .command(CommandDef::<1>::provide("note", "#1"))
+                .map(|c| c.name().to_owned())
+        );
+        assert!(preambule
+            .command(CommandDef::<0>::renew("note", ""))
+            .is_ok());
+
+        assert_eq!(
+            Err(Error::IllegalParameter {
+                name: "pair".to_owned(),
+                number: 3,
+                arity: 2,
+            }),
+            preambule
+                .command(CommandDef::<1>::new("pair", "#1 \\# #2 ##3 #3").default(""))
+                .map(|c| c.name().to_owned())
+        );
+
+        assert_eq!(
+            Err(Error::IllegalCommandName("my_cmd".to_owned())),
+            preambule
+                .command(CommandDef::<0>::new("my_cmd", ""))
+                .map(|c| c.name().to_owned())
+        );
+        assert_eq!(
+            Err(Error::IllegalEnvironmentName("a}b".to_owned())),
+            preambule
+                .environment(EnvironmentDef::<0>::new("a}b", "", ""))
+                .map(|e| e.name().to_owned())
+        );
+        let two_col = preambule
+            .environment(EnvironmentDef::<0>::new("two-col", "", ""))
+            .unwrap();
+        assert!(doc.with(two_col.begin::<&str>([])).try_render().is_ok());
+    }
+}
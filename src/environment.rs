@@ -0,0 +1,161 @@
+use crate::node::Node;
+use crate::{Boxed, Container, Context, Element, Error, Macros, Parameter, Raw};
+use std::fmt;
+
+/// A `\begin{name}..\end{name}` block.
+///
+/// ```
+/// use trylatex::{Container, Element, Environment, Text};
+///
+/// let center = Environment::new("center").with(Text("centered"));
+/// assert_eq!("\\begin{center}\ncentered\n\\end{center}", center.render());
+/// ```
+pub struct Environment<'a> {
+    name: String,
+    begin: Macros,
+    body: Boxed<'a>,
+}
+
+impl Environment<'_> {
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        let mut body = Boxed::new();
+        // the name is checked on visit rather than escaped
+        body.after = body
+            .after
+            .with(Macros::new("end").param(Raw(name.as_ref())));
+
+        Self {
+            name: name.as_ref().to_owned(),
+            begin: Macros::new("begin").param(Raw(name.as_ref())),
+            body,
+        }
+    }
+
+    /// Appends a mandatory argument, e.g. the width of a `minipage`.
+    pub fn param<P: Into<Parameter>>(mut self, parameter: P) -> Self {
+        self.begin = self.begin.param(parameter);
+        self
+    }
+
+    /// Appends an optional argument.
+    pub fn opt<P: Into<Parameter>>(mut self, parameter: P) -> Self {
+        self.begin = self.begin.opt(parameter);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn nodes(&self) -> Vec<Node> {
+        self.body.middle.to_nodes()
+    }
+
+    /// Renders the environment with an extra line after its content.
+    pub(crate) fn render_with_end(
+        &self,
+        end: Option<String>,
+        w: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        match end {
+            Some(end) => {
+                self.begin.render_to(w)?;
+                self.body.prep.render_to(w)?;
+                w.write_char('\n')?;
+                self.body.middle.render_to(w)?;
+                write!(w, "\n{}\n", end)?;
+                self.body.after.render_to(w)
+            }
+            None => self.render_to(w),
+        }
+    }
+}
+
+impl<'a> Container<'a> for Environment<'a> {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.body = self.body.with(e);
+        self
+    }
+}
+
+impl Element for Environment<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        // the `\begin` goes in place of the empty prep area
+        self.begin.render_to(w)?;
+        self.body.render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        check_name(&self.name)?;
+
+        self.body.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Environment {
+            name: self.name.clone(),
+            // the first argument of `\begin` is the name
+            args: self.begin.args()[1..].to_vec(),
+            body: self.nodes(),
+        }
+    }
+}
+
+/// Checks a name doesn't break `\begin{..}`.
+pub(crate) fn check_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() || name.contains(['{', '}', '\\', '%']) {
+        return Err(Error::IllegalEnvironmentName(name.to_owned()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, Text};
+
+    #[test]
+    fn arguments() {
+        let minipage = Environment::new("minipage")
+            .opt("t")
+            .param(Raw("0.5\\textwidth"))
+            .with(Text("left"))
+            .with(Text(" side"));
+
+        assert_eq!(
+            "\\begin{minipage}[t]{0.5\\textwidth}\nleft side\n\\end{minipage}",
+            minipage.render()
+        );
+        assert_eq!("minipage", minipage.name());
+    }
+
+    #[test]
+    fn nested() {
+        let quote = Environment::new("quote").with(Environment::new("center").with(Text("x")));
+
+        assert_eq!(
+            "\\begin{quote}\n\\begin{center}\nx\n\\end{center}\n\\end{quote}",
+            quote.render()
+        );
+    }
+
+    #[test]
+    fn name() {
+        let doc = Document::new().with(Environment::new("align*").with(Text("x")));
+        assert!(doc.try_render().unwrap().contains("\\begin{align*}"));
+
+        let doc = Document::new().with(Environment::new("my_env"));
+        assert!(doc.try_render().is_ok());
+
+        let doc = Document::new().with(Environment::new("a}b"));
+        assert_eq!(
+            Err(Error::IllegalEnvironmentName("a}b".to_owned())),
+            doc.try_render()
+        );
+        assert!(Document::new()
+            .with(Environment::new(""))
+            .try_render()
+            .is_err());
+    }
+}
@@ -0,0 +1,236 @@
+use crate::log::Report;
+use std::{fmt, io};
+
+/// An error raised while building a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The same package was requested with options which can't be used together.
+    OptionClash {
+        package: String,
+        option: String,
+        other: String,
+    },
+    /// The element can't be used in the document class.
+    UnsupportedInClass { element: String, class: String },
+    /// Lists are nested deeper than LaTeX allows.
+    TooDeeplyNested { environment: String, depth: usize },
+    /// A table row doesn't cover exactly the declared columns.
+    ColumnMismatch {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The spec of a `\multicolumn` cell doesn't describe exactly one column.
+    IllegalCellSpec(String),
+    /// `H` placement is combined with other specifiers, it's only valid alone.
+    IllegalPlacement(String),
+    /// A starred heading has a short title, it would be dropped.
+    StarredShortTitle(String),
+    /// The label is defined more than once.
+    DuplicateLabel(String),
+    /// A reference points to a label which isn't defined.
+    UndefinedLabel(String),
+    /// A float is labelled without a caption, the label would refer to
+    /// the enclosing section instead.
+    UncaptionedLabel(String),
+    /// A citation of an entry which isn't in the bibliography.
+    UndefinedCitation(String),
+    /// The citation command isn't provided by the bibliography package.
+    UnsupportedCitation { command: String, package: String },
+    /// Citations are made but the bibliography isn't set up.
+    MissingBibliography,
+    /// The command or environment is already defined in the preambule.
+    DuplicateDefinition(String),
+    /// The body of a definition uses an argument it doesn't take.
+    IllegalParameter {
+        name: String,
+        number: usize,
+        arity: usize,
+    },
+    /// A TikZ coordinate refers to a node which isn't defined before it.
+    UndefinedNode(String),
+    /// The name of a TikZ node has a character which separates coordinates.
+    IllegalNodeName(String),
+    /// The name of a command isn't made of letters and `@`.
+    IllegalCommandName(String),
+    /// The name of an environment is empty or has one of `{`, `}`, `\` and `%`.
+    IllegalEnvironmentName(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OptionClash {
+                package,
+                option,
+                other,
+            } => write!(
+                f,
+                "option clash for package {}: {} conflicts with {}",
+                package, option, other
+            ),
+            Error::UnsupportedInClass { element, class } => {
+                write!(f, "{} is not available in the {} class", element, class)
+            }
+            Error::TooDeeplyNested { environment, depth } => {
+                write!(f, "{} is too deeply nested ({} levels)", environment, depth)
+            }
+            Error::ColumnMismatch {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {} covers {} columns, expected {}",
+                row, found, expected
+            ),
+            Error::StarredShortTitle(command) => {
+                write!(f, "{} has no place for a short title", command)
+            }
+            Error::IllegalCellSpec(spec) => {
+                write!(f, "cell spec {:?} must describe exactly one column", spec)
+            }
+            Error::IllegalPlacement(placement) => {
+                write!(
+                    f,
+                    "placement {} combines H with other specifiers",
+                    placement
+                )
+            }
+            Error::DuplicateLabel(key) => write!(f, "label {} is defined more than once", key),
+            Error::UndefinedLabel(key) => write!(f, "label {} is referenced but not defined", key),
+            Error::UncaptionedLabel(key) => {
+                write!(f, "label {} is on a float without a caption", key)
+            }
+            Error::UndefinedCitation(key) => write!(f, "entry {} is cited but not defined", key),
+            Error::UnsupportedCitation { command, package } => {
+                write!(f, "{} is not provided by {}", command, package)
+            }
+            Error::MissingBibliography => f.write_str("citations are made without a bibliography"),
+            Error::DuplicateDefinition(name) => write!(f, "{} is already defined", name),
+            Error::IllegalParameter {
+                name,
+                number,
+                arity,
+            } => write!(
+                f,
+                "{} uses argument #{} but takes {} arguments",
+                name, number, arity
+            ),
+            Error::UndefinedNode(name) => write!(f, "node {} is referenced but not defined", name),
+            Error::IllegalNodeName(name) => write!(f, "node name {} has one of . , ( )", name),
+            Error::IllegalCommandName(name) => {
+                write!(f, "command name {:?} isn't made of letters and @", name)
+            }
+            Error::IllegalEnvironmentName(name) => {
+                write!(
+                    f,
+                    "environment name {:?} is empty or has one of {{ }} \\ %",
+                    name
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An error in a source file, e.g. a `.bib` or a `.tex` one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Starting from 1.
+    pub line: usize,
+    /// Starting from 1, in characters.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An error raised while filling a [`Template`](crate::template::Template).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The placeholder is given no value.
+    Missing(String),
+    /// The value is given for a placeholder which isn't in the template.
+    Unused(String),
+    /// The placeholder is a block but is given a single value.
+    ExpectedBlock(String),
+    /// The placeholder takes a single value but is given a block.
+    ExpectedValue(String),
+    /// An element failed to render.
+    Fmt(fmt::Error),
+}
+
+impl From<fmt::Error> for TemplateError {
+    fn from(err: fmt::Error) -> Self {
+        TemplateError::Fmt(err)
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Missing(name) => write!(f, "placeholder {} has no value", name),
+            TemplateError::Unused(name) => {
+                write!(f, "{} is not a placeholder of the template", name)
+            }
+            TemplateError::ExpectedBlock(name) => write!(f, "{} is a block, not a value", name),
+            TemplateError::ExpectedValue(name) => write!(f, "{} is a value, not a block", name),
+            TemplateError::Fmt(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// An error raised while compiling a document.
+#[derive(Debug)]
+pub enum BuildError {
+    /// The document isn't valid, see [`Document::try_render`](crate::Document::try_render).
+    Render(Error),
+    Io(io::Error),
+    /// The engine or a tool it needs exited with an error.
+    Failed {
+        program: String,
+        report: Report,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::Render(err) => err.fmt(f),
+            BuildError::Io(err) => err.fmt(f),
+            BuildError::Failed { program, report } => {
+                write!(f, "{} failed", program)?;
+                match report.errors().next() {
+                    Some(diagnostic) => write!(f, ": {}", diagnostic.message),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BuildError::Render(err) => Some(err),
+            BuildError::Io(err) => Some(err),
+            BuildError::Failed { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for BuildError {
+    fn from(err: io::Error) -> Self {
+        BuildError::Io(err)
+    }
+}
@@ -0,0 +1,534 @@
+use std::{fmt, ops};
+
+use crate::{Context, Element, Error, Package};
+
+/// A math formula built from Rust values.
+///
+/// It renders math mode content, so it goes into [`InlineMath`](crate::InlineMath)
+/// or the other math elements.
+///
+/// ```
+/// use trylatex::{Element, Expr};
+///
+/// let x = Expr::sym("x");
+/// let e = (x.clone() + 1).pow(2) / (x - 1);
+/// assert_eq!(r"\frac{(x + 1)^2}{x - 1}", e.render());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// A variable or any other LaTeX atom, e.g. `x` or `\alpha`.
+    Symbol(String),
+    Number(f64),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Frac(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Subscript(Box<Expr>, Box<Expr>),
+    Sum {
+        lower: Option<Box<Expr>>,
+        upper: Option<Box<Expr>>,
+        body: Box<Expr>,
+    },
+    Integral {
+        lower: Option<Box<Expr>>,
+        upper: Option<Box<Expr>>,
+        body: Box<Expr>,
+        var: Box<Expr>,
+    },
+    Root {
+        index: Option<Box<Expr>>,
+        radicand: Box<Expr>,
+    },
+    /// Rows of a `pmatrix`, requires `amsmath`.
+    Matrix(Vec<Vec<Expr>>),
+    /// A function applied to arguments, e.g. `\sin(x)` or `f(x, y)`.
+    Apply(String, Vec<Expr>),
+}
+
+/// Functions which have a LaTeX command setting them upright.
+const FUNCTIONS: &[&str] = &[
+    "arccos", "arcsin", "arctan", "arg", "cos", "cosh", "cot", "coth", "csc", "deg", "det", "dim",
+    "exp", "gcd", "hom", "inf", "ker", "lg", "lim", "liminf", "limsup", "ln", "log", "max", "min",
+    "Pr", "sec", "sin", "sinh", "sup", "tan", "tanh",
+];
+
+impl Expr {
+    pub fn sym<S: AsRef<str>>(s: S) -> Self {
+        Expr::Symbol(s.as_ref().to_owned())
+    }
+
+    pub fn num<N: Into<f64>>(n: N) -> Self {
+        Expr::Number(n.into())
+    }
+
+    pub fn frac<A: Into<Expr>, B: Into<Expr>>(numerator: A, denominator: B) -> Self {
+        Expr::Frac(Box::new(numerator.into()), Box::new(denominator.into()))
+    }
+
+    pub fn pow<E: Into<Expr>>(self, exponent: E) -> Self {
+        Expr::Pow(Box::new(self), Box::new(exponent.into()))
+    }
+
+    pub fn subscript<E: Into<Expr>>(self, index: E) -> Self {
+        Expr::Subscript(Box::new(self), Box::new(index.into()))
+    }
+
+    pub fn sqrt<E: Into<Expr>>(radicand: E) -> Self {
+        Expr::Root {
+            index: None,
+            radicand: Box::new(radicand.into()),
+        }
+    }
+
+    pub fn root<N: Into<Expr>, E: Into<Expr>>(index: N, radicand: E) -> Self {
+        Expr::Root {
+            index: Some(Box::new(index.into())),
+            radicand: Box::new(radicand.into()),
+        }
+    }
+
+    /// `\sum_{lower}^{upper} body`
+    pub fn sum<L, U, B>(lower: L, upper: U, body: B) -> Self
+    where
+        L: Into<Option<Expr>>,
+        U: Into<Option<Expr>>,
+        B: Into<Expr>,
+    {
+        Expr::Sum {
+            lower: lower.into().map(Box::new),
+            upper: upper.into().map(Box::new),
+            body: Box::new(body.into()),
+        }
+    }
+
+    /// `\int_{lower}^{upper} body \, dvar`
+    pub fn integral<L, U, B, V>(lower: L, upper: U, body: B, var: V) -> Self
+    where
+        L: Into<Option<Expr>>,
+        U: Into<Option<Expr>>,
+        B: Into<Expr>,
+        V: Into<Expr>,
+    {
+        Expr::Integral {
+            lower: lower.into().map(Box::new),
+            upper: upper.into().map(Box::new),
+            body: Box::new(body.into()),
+            var: Box::new(var.into()),
+        }
+    }
+
+    pub fn matrix<R, I>(rows: R) -> Self
+    where
+        R: IntoIterator<Item = I>,
+        I: IntoIterator<Item = Expr>,
+    {
+        Expr::Matrix(
+            rows.into_iter()
+                .map(|row| row.into_iter().collect())
+                .collect(),
+        )
+    }
+
+    pub fn apply<S, A>(function: S, args: A) -> Self
+    where
+        S: AsRef<str>,
+        A: IntoIterator<Item = Expr>,
+    {
+        Expr::Apply(function.as_ref().to_owned(), args.into_iter().collect())
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Add(..) | Expr::Sub(..) => ADDITIVE,
+            Expr::Sum { .. } | Expr::Integral { .. } => ADDITIVE,
+            Expr::Mul(..) => MULTIPLICATIVE,
+            Expr::Neg(..) => UNARY,
+            Expr::Number(n) if n.is_sign_negative() && *n != 0.0 => UNARY,
+            Expr::Pow(..) | Expr::Subscript(..) => SCRIPT,
+            _ => ATOM,
+        }
+    }
+
+    fn is_big_operator(&self) -> bool {
+        matches!(self, Expr::Sum { .. } | Expr::Integral { .. })
+    }
+
+    /// Renders the operand of an operation with the given precedence,
+    /// wrapping it in parentheses when it would be read otherwise.
+    fn operand(&self, parent: u8, side: Side) -> String {
+        let precedence = self.precedence();
+        let parens = match side {
+            // a base of a script must be a single atom
+            Side::Base => precedence < ATOM || matches!(self, Expr::Frac(..)),
+            // though `x_i^2` is fine
+            Side::PowerBase => match self {
+                Expr::Subscript(base, _) => base.precedence() < ATOM,
+                _ => precedence < ATOM || matches!(self, Expr::Frac(..)),
+            },
+            // big operators extend to the right as far as possible
+            Side::Left => precedence < parent || self.is_big_operator(),
+            Side::Right => {
+                precedence < parent
+                    || (precedence == parent && parent != MULTIPLICATIVE)
+                    || (precedence == UNARY && parent < UNARY)
+            }
+        };
+
+        let rendered = self.render();
+        if parens {
+            format!("({})", rendered)
+        } else {
+            rendered
+        }
+    }
+}
+
+const ADDITIVE: u8 = 1;
+const MULTIPLICATIVE: u8 = 2;
+const UNARY: u8 = 3;
+const SCRIPT: u8 = 4;
+const ATOM: u8 = 5;
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+    Base,
+    PowerBase,
+}
+
+fn group(e: &Expr) -> String {
+    let rendered = e.render();
+    if rendered.chars().count() == 1 {
+        rendered
+    } else {
+        format!("{{{}}}", rendered)
+    }
+}
+
+fn limits(lower: &Option<Box<Expr>>, upper: &Option<Box<Expr>>) -> String {
+    let mut buf = String::new();
+    if let Some(lower) = lower {
+        buf.push('_');
+        buf.push_str(&group(lower));
+    }
+    if let Some(upper) = upper {
+        buf.push('^');
+        buf.push_str(&group(upper));
+    }
+
+    buf
+}
+
+fn number(n: f64) -> String {
+    if n.is_infinite() && n > 0.0 {
+        "\\infty".to_owned()
+    } else if n.is_infinite() {
+        "-\\infty".to_owned()
+    } else if n.is_nan() {
+        "\\mathrm{NaN}".to_owned()
+    } else {
+        n.to_string()
+    }
+}
+
+impl Element for Expr {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        match self {
+            Expr::Symbol(s) => w.write_str(s),
+            Expr::Number(n) => w.write_str(&number(*n)),
+            Expr::Neg(e) => write!(w, "-{}", e.operand(UNARY, Side::Right)),
+            Expr::Add(a, b) => write!(
+                w,
+                "{} + {}",
+                a.operand(ADDITIVE, Side::Left),
+                // addition is associative so `a + (b + c)` is `a + b + c`
+                match b.as_ref() {
+                    Expr::Add(..) => b.render(),
+                    _ => b.operand(ADDITIVE, Side::Right),
+                }
+            ),
+            Expr::Sub(a, b) => write!(
+                w,
+                "{} - {}",
+                a.operand(ADDITIVE, Side::Left),
+                b.operand(ADDITIVE, Side::Right)
+            ),
+            Expr::Mul(a, b) => {
+                let left = a.operand(MULTIPLICATIVE, Side::Left);
+                let right = b.operand(MULTIPLICATIVE, Side::Right);
+                // juxtaposed digits would read as one number
+                if right.starts_with(|c: char| c.is_ascii_digit()) {
+                    write!(w, "{} \\cdot {}", left, right)
+                } else {
+                    write!(w, "{} {}", left, right)
+                }
+            }
+            Expr::Frac(a, b) => write!(w, "\\frac{{{}}}{{{}}}", a.render(), b.render()),
+            Expr::Pow(base, exp) => {
+                write!(
+                    w,
+                    "{}^{}",
+                    base.operand(SCRIPT, Side::PowerBase),
+                    group(exp)
+                )
+            }
+            Expr::Subscript(base, index) => {
+                write!(w, "{}_{}", base.operand(SCRIPT, Side::Base), group(index))
+            }
+            Expr::Sum { lower, upper, body } => write!(
+                w,
+                "\\sum{} {}",
+                limits(lower, upper),
+                body.operand(MULTIPLICATIVE, Side::Right)
+            ),
+            Expr::Integral {
+                lower,
+                upper,
+                body,
+                var,
+            } => write!(
+                w,
+                "\\int{} {} \\, d{}",
+                limits(lower, upper),
+                body.operand(MULTIPLICATIVE, Side::Right),
+                var.operand(SCRIPT, Side::Base)
+            ),
+            Expr::Root { index, radicand } => match index {
+                Some(index) => write!(w, "\\sqrt[{}]{{{}}}", index.render(), radicand.render()),
+                None => write!(w, "\\sqrt{{{}}}", radicand.render()),
+            },
+            Expr::Matrix(rows) => {
+                let rows = rows
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|e| e.render())
+                            .collect::<Vec<_>>()
+                            .join(" & ")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" \\\\ ");
+                write!(w, "\\begin{{pmatrix}} {} \\end{{pmatrix}}", rows)
+            }
+            Expr::Apply(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|e| e.render())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if FUNCTIONS.contains(&function.as_str()) {
+                    write!(w, "\\{}({})", function, args)
+                } else {
+                    write!(w, "{}({})", function, args)
+                }
+            }
+        }
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        match self {
+            Expr::Symbol(_) | Expr::Number(_) => Ok(()),
+            Expr::Neg(e) => e.visit(ctx),
+            Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Mul(a, b)
+            | Expr::Frac(a, b)
+            | Expr::Pow(a, b)
+            | Expr::Subscript(a, b) => {
+                a.visit(ctx)?;
+                b.visit(ctx)
+            }
+            Expr::Sum { lower, upper, body } => {
+                lower.iter().chain(upper).try_for_each(|e| e.visit(ctx))?;
+                body.visit(ctx)
+            }
+            Expr::Integral {
+                lower,
+                upper,
+                body,
+                var,
+            } => {
+                lower.iter().chain(upper).try_for_each(|e| e.visit(ctx))?;
+                body.visit(ctx)?;
+                var.visit(ctx)
+            }
+            Expr::Root { index, radicand } => {
+                index.iter().try_for_each(|e| e.visit(ctx))?;
+                radicand.visit(ctx)
+            }
+            Expr::Matrix(rows) => {
+                ctx.require(Package::new("amsmath"))?;
+                rows.iter().flatten().try_for_each(|e| e.visit(ctx))
+            }
+            Expr::Apply(_, args) => args.iter().try_for_each(|e| e.visit(ctx)),
+        }
+    }
+}
+
+impl From<&str> for Expr {
+    fn from(s: &str) -> Self {
+        Expr::sym(s)
+    }
+}
+
+impl From<String> for Expr {
+    fn from(s: String) -> Self {
+        Expr::Symbol(s)
+    }
+}
+
+macro_rules! number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Expr {
+                fn from(n: $t) -> Self {
+                    Expr::Number(n as f64)
+                }
+            }
+        )*
+    };
+}
+
+number!(i32, i64, u32, u64, usize, f32, f64);
+
+// Only a single integer type, otherwise `2 * x` is ambiguous.
+macro_rules! operators {
+    ($($t:ty),*) => {
+        $(
+            impl ops::Add<Expr> for $t {
+                type Output = Expr;
+
+                fn add(self, rhs: Expr) -> Expr {
+                    Expr::from(self) + rhs
+                }
+            }
+
+            impl ops::Sub<Expr> for $t {
+                type Output = Expr;
+
+                fn sub(self, rhs: Expr) -> Expr {
+                    Expr::from(self) - rhs
+                }
+            }
+
+            impl ops::Mul<Expr> for $t {
+                type Output = Expr;
+
+                fn mul(self, rhs: Expr) -> Expr {
+                    Expr::from(self) * rhs
+                }
+            }
+
+            impl ops::Div<Expr> for $t {
+                type Output = Expr;
+
+                fn div(self, rhs: Expr) -> Expr {
+                    Expr::from(self) / rhs
+                }
+            }
+        )*
+    };
+}
+
+operators!(i32, f64);
+
+impl<T: Into<Expr>> ops::Add<T> for Expr {
+    type Output = Expr;
+
+    fn add(self, rhs: T) -> Expr {
+        Expr::Add(Box::new(self), Box::new(rhs.into()))
+    }
+}
+
+impl<T: Into<Expr>> ops::Sub<T> for Expr {
+    type Output = Expr;
+
+    fn sub(self, rhs: T) -> Expr {
+        Expr::Sub(Box::new(self), Box::new(rhs.into()))
+    }
+}
+
+impl<T: Into<Expr>> ops::Mul<T> for Expr {
+    type Output = Expr;
+
+    fn mul(self, rhs: T) -> Expr {
+        Expr::Mul(Box::new(self), Box::new(rhs.into()))
+    }
+}
+
+impl<T: Into<Expr>> ops::Div<T> for Expr {
+    type Output = Expr;
+
+    fn div(self, rhs: T) -> Expr {
+        Expr::frac(self, rhs)
+    }
+}
+
+impl ops::Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr::Neg(Box::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x() -> Expr {
+        Expr::sym("x")
+    }
+
+    fn y() -> Expr {
+        Expr::sym("y")
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!("x + y \\cdot 2", (x() + y() * 2).render());
+        assert_eq!("(x + y) \\cdot 2", ((x() + y()) * 2).render());
+        assert_eq!("2 x", (2 * x()).render());
+        assert_eq!("x - (y - 1)", (x() - (y() - 1)).render());
+        assert_eq!("x - y - 1", (x() - y() - 1).render());
+        assert_eq!("x + y + 1", (x() + (y() + 1)).render());
+        assert_eq!("x + (-y)", (x() + -y()).render());
+        assert_eq!("x (-3)", (x() * -3).render());
+        assert_eq!("-(x + y)", (-(x() + y())).render());
+        assert_eq!("(-x)^2", (-x()).pow(2).render());
+        assert_eq!("(x^2)^3", x().pow(2).pow(3).render());
+        assert_eq!("x_i^2", x().subscript("i").pow(2).render());
+        assert_eq!("(\\frac{1}{x})^2", (1 / x()).pow(2).render());
+        assert_eq!("0.5 x", (0.5 * x()).render());
+    }
+
+    #[test]
+    fn constructs() {
+        let i = Expr::sym("i");
+        let sum = Expr::sum(i.clone().subscript(0), Expr::sym("n"), x().subscript(i) + 1);
+        assert_eq!("\\sum_{i_0}^n (x_i + 1)", sum.render());
+        assert_eq!("(\\sum_{i_0}^n (x_i + 1)) + 1", (sum + 1).render());
+
+        let integral = Expr::integral(
+            Expr::num(0),
+            Expr::num(f64::INFINITY),
+            Expr::apply("exp", vec![-x()]),
+            x(),
+        );
+        assert_eq!("\\int_0^{\\infty} \\exp(-x) \\, dx", integral.render());
+
+        assert_eq!("\\sqrt[3]{x + 1}", Expr::root(3, x() + 1).render());
+        assert_eq!("f(x, y)", Expr::apply("f", vec![x(), y()]).render());
+        assert_eq!(
+            "\\begin{pmatrix} 1 & 0 \\\\ 0 & 1 \\end{pmatrix}",
+            Expr::matrix(vec![
+                vec![Expr::num(1), Expr::num(0)],
+                vec![Expr::num(0), Expr::num(1)]
+            ])
+            .render()
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use crate::float::{Float, Position};
+use crate::node::{Node, SubFigureItem};
+use crate::{Area, Container, Context, Element, Error, Label, Macros, Package, Parameter, Raw};
+use std::fmt;
+
+/// An image, `\includegraphics`.
+pub struct Graphics {
+    path: String,
+    options: Vec<(&'static str, Option<String>)>,
+}
+
+impl Graphics {
+    pub fn new<S: AsRef<str>>(path: S) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+            options: Vec::new(),
+        }
+    }
+
+    /// Sets the width, e.g. `5cm` or `0.5\textwidth`.
+    pub fn width<S: AsRef<str>>(self, width: S) -> Self {
+        self.option("width", Some(width.as_ref().to_owned()))
+    }
+
+    pub fn height<S: AsRef<str>>(self, height: S) -> Self {
+        self.option("height", Some(height.as_ref().to_owned()))
+    }
+
+    pub fn scale(self, scale: f64) -> Self {
+        self.option("scale", Some(scale.to_string()))
+    }
+
+    /// Rotates the image counterclockwise by the angle in degrees.
+    pub fn angle(self, angle: f64) -> Self {
+        self.option("angle", Some(angle.to_string()))
+    }
+
+    /// Trims the image from the left, bottom, right and top,
+    /// it has effect on what's visible only with [`Graphics::clip`].
+    pub fn trim<S: AsRef<str>>(self, left: S, bottom: S, right: S, top: S) -> Self {
+        let trim = format!(
+            "{} {} {} {}",
+            left.as_ref(),
+            bottom.as_ref(),
+            right.as_ref(),
+            top.as_ref()
+        );
+        self.option("trim", Some(trim))
+    }
+
+    pub fn clip(self) -> Self {
+        self.option("clip", None)
+    }
+
+    fn option(mut self, key: &'static str, value: Option<String>) -> Self {
+        self.options.retain(|(k, _)| *k != key);
+        self.options.push((key, value));
+        self
+    }
+
+    fn get(&self, key: &str) -> Option<&Option<String>> {
+        self.options
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    fn value(&self, key: &str) -> Option<String> {
+        self.get(key).cloned().flatten()
+    }
+}
+
+impl Element for Graphics {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut m = Macros::new("includegraphics");
+        if !self.options.is_empty() {
+            let options = self
+                .options
+                .iter()
+                .map(|(key, value)| match value {
+                    Some(value) => format!("{}={}", key, value),
+                    None => key.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            m = m.opt(Raw(options));
+        }
+
+        m.param(Raw(&self.path)).render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        ctx.require(Package::new("graphicx"))
+    }
+
+    fn to_node(&self) -> Node {
+        let trim =
+            self.value("trim")
+                .and_then(|trim| match trim.split(' ').collect::<Vec<_>>()[..] {
+                    [left, bottom, right, top] => {
+                        Some([left, bottom, right, top].map(str::to_owned))
+                    }
+                    _ => None,
+                });
+
+        Node::Graphics {
+            path: self.path.clone(),
+            width: self.value("width"),
+            height: self.value("height"),
+            scale: self.value("scale").and_then(|scale| scale.parse().ok()),
+            angle: self.value("angle").and_then(|angle| angle.parse().ok()),
+            trim,
+            clip: self.get("clip").is_some(),
+        }
+    }
+}
+
+/// A floating `figure` with a caption.
+pub struct Figure<'a>(Float<'a>);
+
+impl Figure<'_> {
+    pub fn new() -> Self {
+        Self(Float::new("figure", false))
+    }
+
+    /// Sets where the figure may be placed,
+    /// [`Position::Exactly`] adds the `float` package and must be alone.
+    pub fn placement(mut self, positions: &[Position]) -> Self {
+        self.0.placement(positions);
+        self
+    }
+
+    pub fn caption<P: Into<Parameter>>(mut self, caption: P) -> Self {
+        self.0.caption(caption.into());
+        self
+    }
+
+    /// Sets a caption used in the list of figures.
+    pub fn short<P: Into<Parameter>>(mut self, caption: P) -> Self {
+        self.0.short(caption.into());
+        self
+    }
+
+    /// Labels the figure, references are made with the returned label.
+    /// It goes with the caption, a label without one is an error.
+    pub fn label<S: AsRef<str>>(mut self, key: S) -> (Self, Label) {
+        let label = Label::new(key);
+        self.0.label(label.clone());
+        (self, label)
+    }
+}
+
+impl Default for Figure<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for Figure<'a> {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.0 = self.0.with(e);
+        self
+    }
+}
+
+impl Element for Figure<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.0.render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        self.0.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        self.0.to_node()
+    }
+}
+
+/// A part of a figure with its own caption, `subfigure` of `subcaption`.
+pub struct SubFigure<'a> {
+    width: Option<String>,
+    caption: Option<Parameter>,
+    label: Option<Label>,
+    body: Area<'a>,
+}
+
+impl SubFigure<'_> {
+    pub fn new() -> Self {
+        Self {
+            width: None,
+            caption: None,
+            label: None,
+            body: Area::new(),
+        }
+    }
+
+    /// Sets the width, by default the row of a grid is split evenly.
+    pub fn width<S: AsRef<str>>(mut self, width: S) -> Self {
+        self.width = Some(width.as_ref().to_owned());
+        self
+    }
+
+    pub fn caption<P: Into<Parameter>>(mut self, caption: P) -> Self {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    /// Labels the subfigure, references are made with the returned label.
+    /// It goes with the caption, a label without one is an error.
+    pub fn label<S: AsRef<str>>(mut self, key: S) -> (Self, Label) {
+        let label = Label::new(key);
+        self.label = Some(label.clone());
+        (self, label)
+    }
+
+    fn render_with(&self, width: &str, w: &mut dyn fmt::Write) -> fmt::Result {
+        let width = self.width.as_deref().unwrap_or(width);
+
+        Macros::new("begin")
+            .param("subfigure")
+            .opt("b")
+            .param(Raw(width))
+            .render_to(w)?;
+        w.write_char('\n')?;
+        Macros::new("centering").render_to(w)?;
+        w.write_char('\n')?;
+        self.body.render_to(w)?;
+        if let Some(caption) = &self.caption {
+            w.write_char('\n')?;
+            Macros::new("caption").param(caption.clone()).render_to(w)?;
+            if let Some(label) = &self.label {
+                label.render_to(w)?;
+            }
+        }
+        w.write_char('\n')?;
+        Macros::new("end").param("subfigure").render_to(w)
+    }
+
+    fn to_item(&self) -> SubFigureItem {
+        SubFigureItem {
+            width: self.width.clone(),
+            caption: self.caption.clone(),
+            label: self.label.clone(),
+            body: self.body.to_nodes(),
+        }
+    }
+}
+
+impl Default for SubFigure<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for SubFigure<'a> {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.body = self.body.with(e);
+        self
+    }
+}
+
+impl Element for SubFigure<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.render_with("\\linewidth", w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        ctx.require(Package::new("subcaption"))?;
+        if let Some(label) = &self.label {
+            if self.caption.is_none() {
+                return Err(Error::UncaptionedLabel(label.key().to_owned()));
+            }
+            label.visit(ctx)?;
+        }
+
+        self.body.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        Node::SubFigure(self.to_item())
+    }
+}
+
+/// Subfigures laid out in rows of the given number of columns.
+pub struct SubFigures<'a> {
+    columns: usize,
+    items: Vec<SubFigure<'a>>,
+}
+
+impl<'a> SubFigures<'a> {
+    pub fn new(columns: usize) -> Self {
+        Self {
+            columns: columns.max(1),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn subfigure(mut self, subfigure: SubFigure<'a>) -> Self {
+        self.items.push(subfigure);
+        self
+    }
+}
+
+impl Element for SubFigures<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        // leave a bit of space between columns
+        let width = format!("{:.2}\\textwidth", 0.95 / self.columns as f64);
+
+        for (i, row) in self.items.chunks(self.columns).enumerate() {
+            if i > 0 {
+                // a new paragraph starts a new row
+                w.write_str("\n\n")?;
+            }
+            for (j, subfigure) in row.iter().enumerate() {
+                if j > 0 {
+                    w.write_str("\n\\hfill\n")?;
+                }
+                subfigure.render_with(&width, w)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        self.items.iter().try_for_each(|s| s.visit(ctx))
+    }
+
+    fn to_node(&self) -> Node {
+        Node::SubFigures {
+            columns: self.columns,
+            items: self.items.iter().map(SubFigure::to_item).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Document;
+
+    #[test]
+    fn render() {
+        let figure = Figure::new()
+            .placement(&[Position::Here, Position::Force])
+            .with(
+                Graphics::new("plots/loss.pdf")
+                    .width("0.8\\textwidth")
+                    .angle(90.0)
+                    .trim("1cm", "0cm", "1cm", "0cm")
+                    .clip(),
+            )
+            .caption("Training loss over time")
+            .short("Loss")
+            .label("fig:loss")
+            .0;
+
+        assert_eq!(
+            "\\begin{figure}[h!]
+\\centering
+\\includegraphics[width=0.8\\textwidth,angle=90,trim=1cm 0cm 1cm 0cm,clip]{plots/loss.pdf}
+\\caption[Loss]{Training loss over time}\\label{fig:loss}
+\\end{figure}",
+            figure.render()
+        );
+    }
+
+    #[test]
+    fn subfigures() {
+        let grid = SubFigures::new(2)
+            .subfigure(
+                SubFigure::new()
+                    .with(Graphics::new("a.png"))
+                    .caption("A")
+                    .label("fig:a")
+                    .0,
+            )
+            .subfigure(SubFigure::new().with(Graphics::new("b.png")))
+            .subfigure(SubFigure::new().width("3cm").with(Graphics::new("c.png")));
+
+        assert_eq!(
+            "\\begin{subfigure}[b]{0.47\\textwidth}
+\\centering
+\\includegraphics{a.png}
+\\caption{A}\\label{fig:a}
+\\end{subfigure}
+\\hfill
+\\begin{subfigure}[b]{0.47\\textwidth}
+\\centering
+\\includegraphics{b.png}
+\\end{subfigure}
+
+\\begin{subfigure}[b]{3cm}
+\\centering
+\\includegraphics{c.png}
+\\end{subfigure}",
+            grid.render()
+        );
+
+        let doc = Document::new().with(Figure::new().placement(&[Position::Exactly]).with(grid));
+        assert!(doc.try_render().unwrap().starts_with(
+            "\\documentclass{article}\n\\usepackage{float}\n\\usepackage{subcaption}\n\\usepackage{graphicx}\n"
+        ));
+    }
+}
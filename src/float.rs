@@ -0,0 +1,168 @@
+use std::fmt;
+
+use crate::node::{FloatKind, Node};
+use crate::{Area, Container, Context, Element, Error, Label, Macros, Package, Parameter, Raw};
+
+/// Where a float may be placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Position {
+    /// `h`
+    Here,
+    /// `t`
+    Top,
+    /// `b`
+    Bottom,
+    /// `p`, a separate page of floats
+    Page,
+    /// `!`, ignore the restrictions LaTeX has on placement
+    Force,
+    /// `H`, exactly here, requires the `float` package and can't be combined
+    Exactly,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Position::Here => f.write_str("h"),
+            Position::Top => f.write_str("t"),
+            Position::Bottom => f.write_str("b"),
+            Position::Page => f.write_str("p"),
+            Position::Force => f.write_str("!"),
+            Position::Exactly => f.write_str("H"),
+        }
+    }
+}
+
+/// An environment which floats, like `table` or `figure`.
+pub(crate) struct Float<'a> {
+    environment: &'static str,
+    placement: Vec<Position>,
+    caption: Option<Parameter>,
+    short: Option<Parameter>,
+    caption_above: bool,
+    label: Option<Label>,
+    body: Area<'a>,
+}
+
+impl Float<'_> {
+    pub(crate) fn new(environment: &'static str, caption_above: bool) -> Self {
+        Self {
+            environment,
+            placement: Vec::new(),
+            caption: None,
+            short: None,
+            caption_above,
+            label: None,
+            body: Area::new(),
+        }
+    }
+
+    pub(crate) fn placement(&mut self, positions: &[Position]) {
+        self.placement = positions.to_vec();
+    }
+
+    pub(crate) fn caption(&mut self, caption: Parameter) {
+        self.caption = Some(caption);
+    }
+
+    pub(crate) fn short(&mut self, short: Parameter) {
+        self.short = Some(short);
+    }
+
+    pub(crate) fn label(&mut self, label: Label) {
+        self.label = Some(label);
+    }
+
+    fn render_caption(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let caption = match &self.caption {
+            Some(caption) => caption,
+            None => return Ok(()),
+        };
+
+        let mut m = Macros::new("caption");
+        if let Some(short) = &self.short {
+            m = m.opt(short.clone());
+        }
+        w.write_char('\n')?;
+        m.param(caption.clone()).render_to(w)?;
+        if let Some(label) = &self.label {
+            label.render_to(w)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Container<'a> for Float<'a> {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.body = self.body.with(e);
+        self
+    }
+}
+
+impl Element for Float<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut begin = Macros::new("begin").param(self.environment);
+        if !self.placement.is_empty() {
+            let placement = self
+                .placement
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<String>();
+            begin = begin.opt(Raw(placement));
+        }
+
+        begin.render_to(w)?;
+        w.write_char('\n')?;
+        Macros::new("centering").render_to(w)?;
+        if self.caption_above {
+            self.render_caption(w)?;
+        }
+        w.write_char('\n')?;
+        self.body.render_to(w)?;
+        if !self.caption_above {
+            self.render_caption(w)?;
+        }
+        w.write_char('\n')?;
+        Macros::new("end").param(self.environment).render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        if self.placement.contains(&Position::Exactly) {
+            if self.placement.iter().any(|p| *p != Position::Exactly) {
+                let placement = self.placement.iter().map(|p| p.to_string()).collect();
+                return Err(Error::IllegalPlacement(placement));
+            }
+            ctx.require(Package::new("float"))?;
+        }
+        if let Some(label) = &self.label {
+            if self.caption.is_none() {
+                return Err(Error::UncaptionedLabel(label.key().to_owned()));
+            }
+            label.visit(ctx)?;
+        }
+
+        self.body.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        let kind = match self.environment {
+            "table" => FloatKind::Table,
+            _ => FloatKind::Figure,
+        };
+
+        Node::Float {
+            kind,
+            placement: self.placement.clone(),
+            caption: self.caption.clone(),
+            short: self.short.clone(),
+            label: self.label.clone(),
+            body: self.body.to_nodes(),
+        }
+    }
+}
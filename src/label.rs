@@ -0,0 +1,182 @@
+use crate::node::{Node, RefKind};
+use crate::{Context, Element, Error, Macros, Package, Raw};
+use std::fmt;
+
+/// A key of `\label`.
+///
+/// Labelling an element hands out its label, so references can only be
+/// made to labels which exist. The document checks every referenced label
+/// is defined exactly once.
+///
+/// ```
+/// use trylatex::{Container, Document, Ref, Section, Text};
+///
+/// let (section, intro) = Section::new("Introduction").label("sec:intro");
+/// let doc = Document::new()
+///     .with(section)
+///     .with(Text("See section "))
+///     .with(Ref::new(&intro));
+///
+/// assert!(doc.try_render().is_ok());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Label(String);
+
+impl Label {
+    pub(crate) fn new<S: AsRef<str>>(key: S) -> Self {
+        Self(key.as_ref().to_owned())
+    }
+
+    pub fn key(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Element for Label {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        Macros::new("label").param(Raw(&self.0)).render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        ctx.define(self);
+        Ok(())
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Label {
+            key: self.0.clone(),
+        }
+    }
+}
+
+macro_rules! reference {
+    ($(#[$doc:meta])* $name:ident, $kind:ident, $command:literal $(, $package:literal)?) => {
+        $(#[$doc])*
+        pub struct $name(Label);
+
+        impl $name {
+            pub fn new(label: &Label) -> Self {
+                Self(label.clone())
+            }
+        }
+
+        impl Element for $name {
+            fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+                Macros::new($command).param(Raw(self.0.key())).render_to(w)
+            }
+
+            fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+                $(ctx.require(Package::new($package))?;)?
+                ctx.reference(&self.0);
+                Ok(())
+            }
+
+            fn to_node(&self) -> Node {
+                Node::Reference {
+                    kind: RefKind::$kind,
+                    key: self.0.key().to_owned(),
+                }
+            }
+        }
+    };
+}
+
+reference!(
+    /// The number of a labelled element, `\ref`.
+    Ref,
+    Ref,
+    "ref"
+);
+reference!(
+    /// The page of a labelled element, `\pageref`.
+    PageRef,
+    PageRef,
+    "pageref"
+);
+reference!(
+    /// The number of an equation in parentheses, `\eqref` of `amsmath`.
+    EqRef,
+    EqRef,
+    "eqref",
+    "amsmath"
+);
+reference!(
+    /// The number of a labelled element with its kind, e.g. "figure 2",
+    /// `\cref` of `cleveref`.
+    Cref,
+    Cref,
+    "cref",
+    "cleveref"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Container, Document, Equation, Figure, Section, SubFigure, SubFigures, Table};
+
+    #[test]
+    fn references() {
+        let (equation, eq) = Equation::new(Raw("1 = 1")).label("eq:one");
+        let (figure, fig) = Figure::new().caption("One").label("fig:one");
+        let doc = Document::new()
+            .with(equation)
+            .with(figure)
+            .with(EqRef::new(&eq))
+            .with(Cref::new(&fig))
+            .with(PageRef::new(&fig));
+
+        let rendered = doc.try_render().unwrap();
+        assert!(rendered.starts_with(
+            "\\documentclass{article}\n\\usepackage{amsmath}\n\\usepackage{cleveref}\n"
+        ));
+        assert!(rendered.contains("\\eqref{eq:one}\\cref{fig:one}\\pageref{fig:one}"));
+    }
+
+    #[test]
+    fn dangling() {
+        // a label which was handed out but whose element isn't in the document
+        let (_, missing) = Section::new("B").label("sec:missing");
+        let doc = Document::new()
+            .with(Section::new("A").label("sec:a").0)
+            .with(Ref::new(&missing));
+
+        assert_eq!(
+            Err(Error::UndefinedLabel("sec:missing".to_owned())),
+            doc.try_render()
+        );
+    }
+
+    #[test]
+    fn uncaptioned() {
+        let (figure, fig) = Figure::new().label("fig:one");
+        let doc = Document::new().with(figure).with(Ref::new(&fig));
+
+        assert_eq!(
+            Err(Error::UncaptionedLabel("fig:one".to_owned())),
+            doc.try_render()
+        );
+
+        let (subfigure, fig) = SubFigure::new().label("fig:one");
+        let doc = Document::new()
+            .with(Figure::new().with(SubFigures::new(1).subfigure(subfigure)))
+            .with(Ref::new(&fig));
+
+        assert_eq!(
+            Err(Error::UncaptionedLabel("fig:one".to_owned())),
+            doc.try_render()
+        );
+    }
+
+    #[test]
+    fn duplicate() {
+        let doc = Document::new()
+            .with(Section::new("A").label("dup").0)
+            .with(Table::new().caption("B").label("dup").0);
+
+        assert_eq!(
+            Err(Error::DuplicateLabel("dup".to_owned())),
+            doc.try_render()
+        );
+    }
+}
@@ -1,5 +1,111 @@
+pub mod beamer;
+pub mod bib;
+pub mod build;
+pub mod log;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+pub mod node;
+pub mod parse;
+pub mod template;
+pub mod tikz;
+
+mod cite;
+mod class;
+mod context;
+mod define;
+mod environment;
+mod error;
+mod expr;
+mod figure;
+mod float;
+mod label;
+mod list;
+mod math;
+mod package;
+mod pretty;
+mod section;
+mod table;
+mod write;
+
+pub use cite::{Backend, BibSetup, CitationReport, Cite, CiteCommand};
+pub use class::{ClassOption, DocumentType, FontSize, PaperSize};
+pub use context::Context;
+pub use define::{Command, CommandDef, EnvironmentDef, UserEnvironment};
+pub use environment::Environment;
+pub use error::{BuildError, Error, ParseError, TemplateError};
+pub use expr::Expr;
+pub use figure::{Figure, Graphics, SubFigure, SubFigures};
+pub use float::Position;
+pub use label::{Cref, EqRef, Label, PageRef, Ref};
+pub use list::{Description, Enumerate, Item, Itemize};
+pub use math::{Align, AlignLine, DisplayMath, Equation, InlineMath};
+pub use package::{Package, PackageOption, Packages};
+pub use pretty::{PrettyWriter, RenderConfig};
+pub use section::{Chapter, Paragraph, Part, Section, Subparagraph, Subsection, Subsubsection};
+pub use table::{Cell, Column, Row, Table, Tabular};
+pub use write::IoWriter;
+
+use node::Node;
+use std::{fmt, io};
+
 pub trait Element {
-    fn render(&self) -> String;
+    /// Writes the element, containers write their children one by one
+    /// so no intermediate strings are built.
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result;
+
+    fn render(&self) -> String {
+        let mut buf = String::new();
+        self.render_to(&mut buf)
+            .expect("writing into a String doesn't fail");
+        buf
+    }
+
+    /// Writes the element into a file, a socket, etc.
+    ///
+    /// The writer isn't buffered, see [`io::BufWriter`].
+    fn write_to(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        let mut w = IoWriter::new(w);
+        let result = self.render_to(&mut w);
+        w.finish(result)
+    }
+
+    /// Checks the element against the document before it's rendered.
+    ///
+    /// Elements holding other elements must visit them as well.
+    fn visit(&self, _ctx: &mut Context) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The element as data, see [`node`].
+    ///
+    /// Elements which have no node of their own are kept as the LaTeX they render.
+    fn to_node(&self) -> Node {
+        Node::Raw {
+            latex: self.render(),
+        }
+    }
+}
+
+impl<E: Element + ?Sized> Element for Box<E> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        (**self).render_to(w)
+    }
+
+    fn render(&self) -> String {
+        (**self).render()
+    }
+
+    fn write_to(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        (**self).write_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        (**self).visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        (**self).to_node()
+    }
 }
 
 pub trait Container<'a> {
@@ -8,99 +114,375 @@ pub trait Container<'a> {
 
 pub struct Document<'a> {
     preambule: Preambule,
-    body: Boxed<'a>,
+    body: Environment<'a>,
 }
 
 impl Document<'_> {
     pub fn new() -> Self {
-        let mut body = Boxed::new();
-        body.prep = body.prep.with(Macros::new("begin").param("document"));
-        body.after = body.after.with(Macros::new("end").param("document"));
-
         Self {
             preambule: Preambule::new(),
-            body,
+            body: Environment::new("document"),
         }
     }
 
     pub fn preambule(&mut self) -> &mut Preambule {
         &mut self.preambule
     }
+
+    /// Renders the document after checking its elements
+    /// are valid for it.
+    pub fn try_render(&self) -> Result<String, Error> {
+        let packages = self.check()?;
+
+        let mut buf = String::new();
+        self.render_with(&packages, &mut buf)
+            .expect("writing into a String doesn't fail");
+        Ok(buf)
+    }
+
+    /// Checks the document like [`Document::try_render`]
+    /// and lays it out with the configuration.
+    pub fn try_render_with(&self, config: &RenderConfig) -> Result<String, Error> {
+        let packages = self.check()?;
+
+        let mut buf = String::new();
+        let mut w = PrettyWriter::new(config, &mut buf);
+        self.render_with(&packages, &mut w)
+            .and_then(|_| w.finish())
+            .expect("writing into a String doesn't fail");
+        Ok(buf)
+    }
+
+    /// Checks the document like [`Document::try_render`] and streams it,
+    /// an invalid document is an [`io::ErrorKind::InvalidData`] error
+    /// which wraps [`Error`].
+    pub fn try_write_to(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        let packages = self
+            .check()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let mut w = IoWriter::new(w);
+        let result = self.render_with(&packages, &mut w);
+        w.finish(result)
+    }
+
+    /// Compares citations with the entries of the bibliography,
+    /// e.g. one read by [`bib::parse`].
+    pub fn citation_report(&self) -> Result<CitationReport, Error> {
+        let mut ctx = Context::new(&self.preambule);
+        self.body.visit(&mut ctx)?;
+
+        let entries = self
+            .preambule
+            .bibliography
+            .as_ref()
+            .and_then(|b| b.get_entries());
+
+        Ok(CitationReport::new(entries, ctx.citations()))
+    }
+
+    /// Visits the body and returns the packages the document needs.
+    fn check(&self) -> Result<Packages, Error> {
+        let class = &self.preambule.r#type;
+        if !class.has_frames() {
+            let themes = [
+                ("\\usetheme", &self.preambule.theme),
+                ("\\usecolortheme", &self.preambule.color_theme),
+            ];
+            if let Some((element, _)) = themes.iter().find(|(_, theme)| theme.is_some()) {
+                return Err(Error::UnsupportedInClass {
+                    element: element.to_string(),
+                    class: class.to_string(),
+                });
+            }
+        }
+
+        let mut ctx = Context::new(&self.preambule);
+        self.body.visit(&mut ctx)?;
+        ctx.check_labels()?;
+        match &self.preambule.bibliography {
+            Some(setup) => setup.check(ctx.citations())?,
+            None if !ctx.citations().is_empty() => return Err(Error::MissingBibliography),
+            None => {}
+        }
+
+        let mut packages = self.preambule.packages.clone();
+        for package in ctx.packages().iter() {
+            packages.add(package.clone())?;
+        }
+
+        Ok(packages)
+    }
+
+    fn render_with(&self, packages: &Packages, w: &mut dyn fmt::Write) -> fmt::Result {
+        let end = self.preambule.bibliography.as_ref().map(|b| b.render_end());
+
+        self.preambule.render_with(packages, w)?;
+        w.write_str("\n\n")?;
+        self.body.render_with_end(end, w)?;
+        w.write_str("\n")
+    }
+}
+
+impl Default for Document<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> Container<'a> for Document<'a> {
     fn with<E: Element + 'a>(mut self, e: E) -> Self {
-        self.body.middle = self.body.middle.with(e);
+        self.body = self.body.with(e);
         self
     }
 }
 
 impl Element for Document<'_> {
+    /// Renders the document with the packages its elements require,
+    /// an invalid document is a [`fmt::Error`] which can't tell why,
+    /// see [`Document::try_render`].
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let packages = self.check().map_err(|_| fmt::Error)?;
+        self.render_with(&packages, w)
+    }
+
+    /// # Panics
+    ///
+    /// If the document is invalid, [`Document::try_render`] returns the error instead.
     fn render(&self) -> String {
-        self.preambule.render() + "\n\n" + &self.body.render() + "\n"
+        match self.try_render() {
+            Ok(rendered) => rendered,
+            Err(err) => panic!("the document is invalid: {}", err),
+        }
+    }
+
+    /// Same as [`Document::try_write_to`].
+    fn write_to(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        self.try_write_to(w)
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Macros {
+    #[cfg_attr(feature = "serde", serde(rename = "name"))]
     m: String,
-    params: Vec<Parameter>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    starred: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    args: Vec<Argument>,
+}
+
+/// An argument of a macros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Argument {
+    /// Rendered in curly braces `{..}`.
+    Mandatory(Parameter),
+    /// Rendered in square brackets `[..]`.
+    Optional(Parameter),
 }
 
 impl Macros {
     pub fn new<S: AsRef<str>>(m: S) -> Self {
         Self {
             m: m.as_ref().to_owned(),
-            params: Vec::new(),
+            starred: false,
+            args: Vec::new(),
         }
     }
 
+    /// Appends a mandatory argument.
     pub fn param<P: Into<Parameter>>(mut self, parameter: P) -> Self {
-        self.params.push(parameter.into());
+        self.args.push(Argument::Mandatory(parameter.into()));
         self
     }
+
+    /// Appends an optional argument.
+    pub fn opt<P: Into<Parameter>>(mut self, parameter: P) -> Self {
+        self.args.push(Argument::Optional(parameter.into()));
+        self
+    }
+
+    /// Uses the starred form of the macros, e.g. `\section*`.
+    pub fn star(mut self) -> Self {
+        self.starred = true;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.m
+    }
+
+    pub fn is_starred(&self) -> bool {
+        self.starred
+    }
+
+    pub fn args(&self) -> &[Argument] {
+        &self.args
+    }
 }
 
 impl Element for Macros {
-    fn render(&self) -> String {
-        let params = self.params.iter().map(|p| p.render()).collect::<String>();
-        if !params.is_empty() {
-            format!("\\{}{{{}}}", self.m.clone(), params)
-        } else {
-            format!("\\{}", self.m.clone())
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        write!(w, "\\{}", self.m)?;
+        if self.starred {
+            w.write_char('*')?;
         }
+
+        self.args.iter().try_for_each(|arg| arg.render_to(w))
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Macros(self.clone())
+    }
+}
+
+impl Element for Argument {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        match self {
+            Self::Mandatory(p) => {
+                w.write_char('{')?;
+                p.render_to(w)?;
+                w.write_char('}')
+            }
+            Self::Optional(p) => {
+                let mut bracket = BracketFinder(false);
+                p.render_to(&mut bracket)?;
+                // a `]` would close the optional argument too early
+                if bracket.0 {
+                    w.write_str("[{")?;
+                    p.render_to(w)?;
+                    w.write_str("}]")
+                } else {
+                    w.write_char('[')?;
+                    p.render_to(w)?;
+                    w.write_char(']')
+                }
+            }
+        }
+    }
+}
+
+/// Drops what's written, only telling whether there's a `]`.
+struct BracketFinder(bool);
+
+impl fmt::Write for BracketFinder {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 = self.0 || s.contains(']');
+        Ok(())
     }
 }
 
-pub struct Text<S: AsRef<str>>(S);
+/// Plain text, LaTeX special characters are escaped.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Text<S: AsRef<str>>(pub S);
 
 impl<S: AsRef<str>> Element for Text<S> {
-    fn render(&self) -> String {
-        self.0.as_ref().to_owned()
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        escape_to(self.0.as_ref(), w)
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Text {
+            text: self.0.as_ref().to_owned(),
+        }
     }
 }
 
-pub struct Preambule {
-    r#type: DocumentType,
-    author: Option<Parameter>,
-    tittle: Option<Parameter>,
+/// Hand-written LaTeX, emitted verbatim.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Raw<S: AsRef<str>>(pub S);
+
+impl<S: AsRef<str>> Element for Raw<S> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_str(self.0.as_ref())
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Raw {
+            latex: self.0.as_ref().to_owned(),
+        }
+    }
 }
 
-pub enum DocumentType {
-    Article,
+/// Escapes characters which have a special meaning in LaTeX
+/// so the string is typeset as is.
+pub fn escape<S: AsRef<str>>(s: S) -> String {
+    let s = s.as_ref();
+    let mut buf = String::with_capacity(s.len());
+    escape_to(s, &mut buf).expect("writing into a String doesn't fail");
+
+    buf
 }
 
-impl std::fmt::Display for DocumentType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            DocumentType::Article => f.write_str("article"),
-        }
+fn escape_to(s: &str, w: &mut dyn fmt::Write) -> fmt::Result {
+    let mut plain = 0;
+    for (i, c) in s.char_indices() {
+        let escaped = match c {
+            '\\' => "\\textbackslash{}",
+            '~' => "\\textasciitilde{}",
+            '^' => "\\textasciicircum{}",
+            '%' => "\\%",
+            '&' => "\\&",
+            '$' => "\\$",
+            '#' => "\\#",
+            '_' => "\\_",
+            '{' => "\\{",
+            '}' => "\\}",
+            _ => continue,
+        };
+
+        w.write_str(&s[plain..i])?;
+        w.write_str(escaped)?;
+        plain = i + c.len_utf8();
     }
+
+    w.write_str(&s[plain..])
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Preambule {
+    r#type: DocumentType,
+    class_options: Vec<ClassOption>,
+    packages: Packages,
+    bibliography: Option<BibSetup>,
+    theme: Option<String>,
+    color_theme: Option<String>,
+    tikz_libraries: Vec<String>,
+    lines: Vec<Parameter>,
+    /// Lines which go right before the package of the index,
+    /// they keep a parsed preambule in the source order.
+    pinned: Vec<(usize, Parameter)>,
+    /// Commands and environments defined by the lines, with their arguments.
+    defined: Vec<(String, (usize, bool))>,
+    author: Option<Parameter>,
+    #[cfg_attr(feature = "serde", serde(rename = "title"))]
+    tittle: Option<Parameter>,
 }
 
 impl Preambule {
     pub fn new() -> Self {
         Self {
             r#type: DocumentType::Article,
+            class_options: Vec::new(),
+            packages: Packages::new(),
+            bibliography: None,
+            theme: None,
+            color_theme: None,
+            tikz_libraries: Vec::new(),
+            lines: Vec::new(),
+            pinned: Vec::new(),
+            defined: Vec::new(),
             author: None,
             tittle: None,
         }
@@ -111,11 +493,145 @@ impl Preambule {
         self
     }
 
+    /// Sets an option of the document class,
+    /// replacing an option which sets the same thing.
+    pub fn class_option<O: Into<ClassOption>>(&mut self, option: O) -> &mut Self {
+        let option = option.into();
+        match self.class_options.iter_mut().find(|o| o.overrides(&option)) {
+            Some(present) => *present = option,
+            None => self.class_options.push(option),
+        }
+        self
+    }
+
+    /// Requests a package, merging options with an earlier request of it.
+    pub fn package(&mut self, package: Package) -> Result<&mut Self, Error> {
+        self.packages.add(package)?;
+        Ok(self)
+    }
+
+    /// Requests a package without options.
+    pub fn use_package<S: AsRef<str>>(&mut self, name: S) -> &mut Self {
+        self.packages
+            .add(Package::new(name))
+            .expect("a package without options can't clash");
+        self
+    }
+
+    /// Sets up the bibliography, adding the package it needs
+    /// in place of the one of an earlier setup.
+    pub fn bibliography(&mut self, setup: BibSetup) -> Result<&mut Self, Error> {
+        let mut packages = self.packages.clone();
+        if let Some(previous) = &self.bibliography {
+            packages.remove(previous.package().name());
+        }
+        packages.add(setup.package())?;
+
+        self.packages = packages;
+        self.bibliography = Some(setup);
+        Ok(self)
+    }
+
+    /// `\usetheme` of a presentation.
+    pub fn theme<S: AsRef<str>>(&mut self, theme: S) -> &mut Self {
+        self.theme = Some(theme.as_ref().to_owned());
+        self
+    }
+
+    /// `\usecolortheme` of a presentation.
+    pub fn color_theme<S: AsRef<str>>(&mut self, theme: S) -> &mut Self {
+        self.color_theme = Some(theme.as_ref().to_owned());
+        self
+    }
+
+    /// Loads a TikZ library, adding the `tikz` package.
+    pub fn tikz_library<S: AsRef<str>>(&mut self, library: S) -> &mut Self {
+        let library = library.as_ref();
+        if !self.tikz_libraries.iter().any(|l| l == library) {
+            self.tikz_libraries.push(library.to_owned());
+        }
+        self.use_package("tikz")
+    }
+
+    /// Adds anything else to the preambule,
+    /// lines go after packages in the order they were added.
+    pub fn line<P: Into<Parameter>>(&mut self, line: P) -> &mut Self {
+        self.lines.push(line.into());
+        self
+    }
+
+    /// Adds a line which goes before the packages requested after it.
+    pub(crate) fn line_before_packages<P: Into<Parameter>>(&mut self, line: P) -> &mut Self {
+        let at = self.packages.iter().count();
+        self.pinned.push((at, line.into()));
+        self
+    }
+
+    /// Defines a command, the handle calls it with the declared arguments.
+    pub fn command<const N: usize, const OPT: bool>(
+        &mut self,
+        def: CommandDef<N, OPT>,
+    ) -> Result<Command<N, OPT>, Error> {
+        let (kind, name) = (def.kind(), def.name().to_owned());
+        let (m, command) = def.define()?;
+
+        self.declare(kind, vec![(name, (N, OPT))])?;
+        self.lines.push(m.into());
+        Ok(command)
+    }
+
+    /// Defines an environment, the handle begins it with the declared arguments.
+    pub fn environment<const N: usize, const OPT: bool>(
+        &mut self,
+        def: EnvironmentDef<N, OPT>,
+    ) -> Result<UserEnvironment<N, OPT>, Error> {
+        let (kind, name) = (def.kind(), def.name().to_owned());
+        let (m, environment) = def.define()?;
+
+        // LaTeX defines `\name` and `\endname`
+        let end = format!("end{}", name);
+        self.declare(kind, vec![(name, (N, OPT)), (end, (0, false))])?;
+        self.lines.push(m.into());
+        Ok(environment)
+    }
+
+    fn declare(
+        &mut self,
+        kind: define::Kind,
+        names: Vec<(String, (usize, bool))>,
+    ) -> Result<(), Error> {
+        for (name, signature) in &names {
+            let present = self.defined.iter().find(|(n, _)| n == name);
+            match (kind, present) {
+                (define::Kind::New, Some(_)) => {
+                    return Err(Error::DuplicateDefinition(name.clone()))
+                }
+                (define::Kind::Provide, Some((_, s))) if s != signature => {
+                    return Err(Error::DuplicateDefinition(name.clone()))
+                }
+                _ => {}
+            }
+        }
+
+        for (name, signature) in names {
+            match self.defined.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, present)) => *present = signature,
+                None => self.defined.push((name, signature)),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn packages(&self) -> &Packages {
+        &self.packages
+    }
+
     pub fn tittle<P>(&mut self, parameter: P) -> &mut Self
     where
         P: Into<Parameter>,
     {
-        self.tittle = Some(Macros::new("title").param(parameter.into()).into());
+        self.tittle = Some(parameter.into());
         self
     }
 
@@ -123,21 +639,89 @@ impl Preambule {
     where
         S: AsRef<str>,
     {
-        self.author = Some(Macros::new("author").param(author).into());
+        self.author = Some(author.as_ref().into());
         self
     }
 }
 
-impl Element for Preambule {
-    fn render(&self) -> String {
-        let mut buf = Vec::new();
+impl Default for Preambule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Preambule {
+    fn render_with(&self, packages: &Packages, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut class = Macros::new("documentclass");
+        if !self.class_options.is_empty() {
+            let options = self
+                .class_options
+                .iter()
+                .map(|o| o.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            class = class.opt(Raw(options));
+        }
+        class.param(Raw(self.r#type.to_string())).render_to(w)?;
+
+        let mut pinned = self.pinned.iter().peekable();
+        for (i, package) in packages.iter().enumerate() {
+            while let Some((_, line)) = pinned.next_if(|(at, _)| *at <= i) {
+                w.write_char('\n')?;
+                line.render_to(w)?;
+            }
+            w.write_char('\n')?;
+            package.render_to(w)?;
+        }
+        for (_, line) in pinned {
+            w.write_char('\n')?;
+            line.render_to(w)?;
+        }
+        if !self.tikz_libraries.is_empty() {
+            w.write_char('\n')?;
+            Macros::new("usetikzlibrary")
+                .param(Raw(self.tikz_libraries.join(",")))
+                .render_to(w)?;
+        }
+        if let Some(resource) = self
+            .bibliography
+            .as_ref()
+            .and_then(|b| b.render_preambule())
+        {
+            w.write_char('\n')?;
+            w.write_str(&resource)?;
+        }
+        if let Some(theme) = &self.theme {
+            w.write_char('\n')?;
+            Macros::new("usetheme").param(Raw(theme)).render_to(w)?;
+        }
+        if let Some(theme) = &self.color_theme {
+            w.write_char('\n')?;
+            Macros::new("usecolortheme")
+                .param(Raw(theme))
+                .render_to(w)?;
+        }
 
-        buf.push(format!("\\documentclass{{{}}}", self.r#type));
+        for line in &self.lines {
+            w.write_char('\n')?;
+            line.render_to(w)?;
+        }
+        if let Some(tittle) = &self.tittle {
+            w.write_char('\n')?;
+            Macros::new("title").param(tittle.clone()).render_to(w)?;
+        }
+        if let Some(author) = &self.author {
+            w.write_char('\n')?;
+            Macros::new("author").param(author.clone()).render_to(w)?;
+        }
 
-        self.tittle.as_ref().map(|tittle| buf.push(tittle.render()));
-        self.author.as_ref().map(|author| buf.push(author.render()));
+        Ok(())
+    }
+}
 
-        buf.join("\n")
+impl Element for Preambule {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.render_with(&self.packages, w)
     }
 }
 
@@ -146,6 +730,7 @@ pub fn LaTeX() -> Macros {
     Macros::new("LaTeX")
 }
 
+/// Content surrounded by a prefix and a suffix.
 pub struct Boxed<'a> {
     prep: Area<'a>,
     middle: Area<'a>,
@@ -153,7 +738,7 @@ pub struct Boxed<'a> {
 }
 
 impl Boxed<'_> {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             prep: Area::new(),
             middle: Area::new(),
@@ -162,15 +747,32 @@ impl Boxed<'_> {
     }
 }
 
+impl Default for Boxed<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> Container<'a> for Boxed<'a> {
-    fn with<E: Element + 'a>(self, e: E) -> Self {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.middle = self.middle.with(e);
         self
     }
 }
 
 impl Element for Boxed<'_> {
-    fn render(&self) -> String {
-        self.prep.render() + "\n" + &self.middle.render() + "\n" + &self.after.render()
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.prep.render_to(w)?;
+        w.write_char('\n')?;
+        self.middle.render_to(w)?;
+        w.write_char('\n')?;
+        self.after.render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        self.prep.visit(ctx)?;
+        self.middle.visit(ctx)?;
+        self.after.visit(ctx)
     }
 }
 
@@ -182,6 +784,23 @@ impl Area<'_> {
     pub fn new() -> Self {
         Self { objs: Vec::new() }
     }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.objs.is_empty()
+    }
+
+    pub(crate) fn to_nodes(&self) -> Vec<Node> {
+        self.objs
+            .iter()
+            .flat_map(|obj| node::flatten(obj.to_node()))
+            .collect()
+    }
+}
+
+impl Default for Area<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> Container<'a> for Area<'a> {
@@ -192,14 +811,38 @@ impl<'a> Container<'a> for Area<'a> {
 }
 
 impl Element for Area<'_> {
-    fn render(&self) -> String {
-        self.objs.iter().map(|obj| obj.render()).collect()
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.objs.iter().try_for_each(|obj| obj.render_to(w))
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        self.objs.iter().try_for_each(|obj| obj.visit(ctx))
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Sequence {
+            body: self.to_nodes(),
+        }
     }
 }
 
+/// An argument of a macros.
+///
+/// Serialized, a literal is a string and the others are
+/// `{"raw": ".."}` and `{"macros": {..}}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Parameter {
-    Literal(String),
+    /// LaTeX code, rendered verbatim.
+    Raw(String),
     Macros(Macros),
+    /// Plain text, escaped on render.
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Literal(String),
 }
 
 impl<S: AsRef<str>> From<S> for Parameter {
@@ -208,17 +851,32 @@ impl<S: AsRef<str>> From<S> for Parameter {
     }
 }
 
-impl Into<Parameter> for Macros {
-    fn into(self) -> Parameter {
-        Parameter::Macros(self)
+impl<S: AsRef<str>> From<Raw<S>> for Parameter {
+    fn from(raw: Raw<S>) -> Parameter {
+        Parameter::Raw(raw.0.as_ref().to_owned())
+    }
+}
+
+impl From<Macros> for Parameter {
+    fn from(m: Macros) -> Parameter {
+        Parameter::Macros(m)
     }
 }
 
 impl Element for Parameter {
-    fn render(&self) -> String {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
         match self {
-            Self::Literal(l) => l.clone(),
-            Self::Macros(m) => m.render(),
+            Self::Literal(l) => escape_to(l, w),
+            Self::Raw(r) => w.write_str(r),
+            Self::Macros(m) => m.render_to(w),
+        }
+    }
+
+    fn to_node(&self) -> Node {
+        match self {
+            Self::Literal(l) => Node::Text { text: l.clone() },
+            Self::Raw(r) => Node::Raw { latex: r.clone() },
+            Self::Macros(m) => Node::Macros(m.clone()),
         }
     }
 }
@@ -246,4 +904,93 @@ something
         let rendered = doc.render();
         assert_eq!(expected, rendered)
     }
+
+    #[test]
+    fn preambule_packages() {
+        let mut preambule = Preambule::new();
+        preambule
+            .use_package("amsmath")
+            .package(Package::new("inputenc").option("utf8"))
+            .unwrap()
+            .use_package("amsmath");
+
+        assert_eq!(
+            "\\documentclass{article}\n\\usepackage{amsmath}\n\\usepackage[utf8]{inputenc}",
+            preambule.render()
+        );
+        assert!(preambule
+            .package(Package::new("inputenc").option("latin1"))
+            .is_err());
+    }
+
+    #[test]
+    fn preambule_class_options() {
+        let mut preambule = Preambule::new();
+        preambule
+            .r#type(DocumentType::ScrReport)
+            .class_option(PaperSize::A4)
+            .class_option(FontSize::Pt11)
+            .class_option(ClassOption::TwoColumn)
+            .class_option(ClassOption::OneColumn)
+            .class_option(FontSize::Pt12);
+
+        assert_eq!(
+            "\\documentclass[a4paper,12pt,onecolumn]{scrreprt}",
+            preambule.render()
+        );
+
+        preambule.r#type(DocumentType::custom("acmart"));
+        assert!(preambule.render().ends_with("{acmart}"));
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let rendered = Text(r"50% of $x_1 & {y} ~ #2^n \").render();
+        assert_eq!(
+            r"50\% of \$x\_1 \& \{y\} \textasciitilde{} \#2\textasciicircum{}n \textbackslash{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn raw_is_verbatim() {
+        assert_eq!(r"\emph{a_b}", Raw(r"\emph{a_b}").render());
+        assert_eq!(
+            r"\author{\emph{Me}}",
+            Macros::new("author").param(Raw(r"\emph{Me}")).render()
+        );
+        assert_eq!(
+            r"\author{A\_B}",
+            Macros::new("author").param("A_B").render()
+        );
+    }
+
+    #[test]
+    fn macros_arguments() {
+        assert_eq!(
+            r"\newcommand{a}{b}",
+            Macros::new("newcommand").param("a").param("b").render()
+        );
+        assert_eq!(
+            r"\includegraphics[width=3cm]{img.png}",
+            Macros::new("includegraphics")
+                .opt("width=3cm")
+                .param("img.png")
+                .render()
+        );
+        assert_eq!(
+            r"\section*{Intro}",
+            Macros::new("section").star().param("Intro").render()
+        );
+        assert_eq!(
+            r"\item[{[a]}]",
+            Macros::new("item").opt(Raw("[a]")).render()
+        );
+        assert_eq!(
+            r"\item[{\textbf{]}}]",
+            Macros::new("item")
+                .opt(Macros::new("textbf").param(Raw("]")))
+                .render()
+        );
+    }
 }
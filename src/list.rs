@@ -0,0 +1,328 @@
+use crate::beamer::Overlay;
+use crate::node::{ListItem, ListKind, Node};
+use crate::{Area, Argument, Container, Context, Element, Error, Macros, Package, Parameter, Raw};
+use std::fmt;
+
+/// LaTeX allows 4 levels of `itemize` and `enumerate`.
+const MAX_DEPTH: usize = 4;
+/// LaTeX allows 6 levels of lists of any kind.
+const MAX_LIST_DEPTH: usize = 6;
+
+/// An `\item` of a list.
+pub struct Item<'a> {
+    label: Option<Parameter>,
+    overlay: Option<Overlay>,
+    body: Area<'a>,
+}
+
+impl Item<'_> {
+    pub fn new() -> Self {
+        Self {
+            label: None,
+            overlay: None,
+            body: Area::new(),
+        }
+    }
+
+    /// Replaces the bullet or number of the item,
+    /// for a description it's the term being described.
+    pub fn label<P: Into<Parameter>>(mut self, label: P) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Shows the item only on some slides of a presentation.
+    pub fn overlay(mut self, overlay: Overlay) -> Self {
+        self.overlay = Some(overlay);
+        self
+    }
+
+    fn to_list_item(&self) -> ListItem {
+        ListItem {
+            label: self.label.clone(),
+            overlay: self.overlay.clone(),
+            body: self.body.to_nodes(),
+        }
+    }
+}
+
+impl Default for Item<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for Item<'a> {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.body = self.body.with(e);
+        self
+    }
+}
+
+impl Element for Item<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        Macros::new("item").render_to(w)?;
+        if let Some(overlay) = &self.overlay {
+            overlay.render_to(w)?;
+        }
+        if let Some(label) = &self.label {
+            Argument::Optional(label.clone()).render_to(w)?;
+        }
+
+        if !self.body.is_empty() {
+            w.write_char(' ')?;
+            self.body.render_to(w)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        if let Some(overlay) = &self.overlay {
+            overlay.visit(ctx)?;
+        }
+
+        self.body.visit(ctx)
+    }
+}
+
+struct List<'a> {
+    environment: &'static str,
+    // `enumitem` options
+    options: Vec<(&'static str, String)>,
+    items: Vec<Item<'a>>,
+}
+
+impl<'a> List<'a> {
+    fn new(environment: &'static str) -> Self {
+        Self {
+            environment,
+            options: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    fn option(&mut self, key: &'static str, value: String) {
+        self.options.retain(|(k, _)| *k != key);
+        self.options.push((key, value));
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl Element for List<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut begin = Macros::new("begin").param(self.environment);
+        if !self.options.is_empty() {
+            let options = self
+                .options
+                .iter()
+                .map(|(key, value)| format!("{}={{{}}}", key, value))
+                .collect::<Vec<_>>()
+                .join(",");
+            begin = begin.opt(Raw(options));
+        }
+
+        begin.render_to(w)?;
+        for item in &self.items {
+            w.write_char('\n')?;
+            item.render_to(w)?;
+        }
+        w.write_char('\n')?;
+        Macros::new("end").param(self.environment).render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        if !self.options.is_empty() {
+            ctx.require(Package::new("enumitem"))?;
+        }
+
+        let depth = ctx.enter(self.environment);
+        let lists = ctx.enter("list");
+        let limit = match self.environment {
+            "description" => MAX_LIST_DEPTH,
+            _ => MAX_DEPTH,
+        };
+        if depth > limit || lists > MAX_LIST_DEPTH {
+            return Err(Error::TooDeeplyNested {
+                environment: self.environment.to_owned(),
+                depth: depth.max(lists),
+            });
+        }
+
+        self.items.iter().try_for_each(|item| item.visit(ctx))?;
+
+        ctx.leave("list");
+        ctx.leave(self.environment);
+
+        Ok(())
+    }
+
+    fn to_node(&self) -> Node {
+        let kind = match self.environment {
+            "itemize" => ListKind::Itemize,
+            "enumerate" => ListKind::Enumerate,
+            _ => ListKind::Description,
+        };
+
+        Node::List {
+            kind,
+            label: self.get("label").map(str::to_owned),
+            start: self.get("start").and_then(|start| start.parse().ok()),
+            items: self.items.iter().map(Item::to_list_item).collect(),
+        }
+    }
+}
+
+macro_rules! list {
+    ($(#[$doc:meta])* $name:ident, $environment:literal) => {
+        $(#[$doc])*
+        ///
+        /// Each element added with [`Container::with`] becomes an item.
+        pub struct $name<'a>(List<'a>);
+
+        impl<'a> $name<'a> {
+            pub fn new() -> Self {
+                Self(List::new($environment))
+            }
+
+            pub fn item(mut self, item: Item<'a>) -> Self {
+                self.0.items.push(item);
+                self
+            }
+        }
+
+        impl Default for $name<'_> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<'a> Container<'a> for $name<'a> {
+            fn with<E: Element + 'a>(self, e: E) -> Self {
+                self.item(Item::new().with(e))
+            }
+        }
+
+        impl Element for $name<'_> {
+            fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+                self.0.render_to(w)
+            }
+
+            fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+                self.0.visit(ctx)
+            }
+
+            fn to_node(&self) -> Node {
+                self.0.to_node()
+            }
+        }
+    };
+}
+
+list!(
+    /// A bulleted list.
+    Itemize,
+    "itemize"
+);
+list!(
+    /// A numbered list.
+    Enumerate,
+    "enumerate"
+);
+list!(
+    /// A list of terms and their descriptions, the term is set by [`Item::label`].
+    Description,
+    "description"
+);
+
+impl Itemize<'_> {
+    /// Sets the bullet of every item, requires `enumitem`.
+    pub fn label<S: AsRef<str>>(mut self, label: S) -> Self {
+        self.0.option("label", label.as_ref().to_owned());
+        self
+    }
+}
+
+impl Enumerate<'_> {
+    /// Sets the format of numbers, e.g. `(\alph*)`, requires `enumitem`.
+    pub fn label<S: AsRef<str>>(mut self, label: S) -> Self {
+        self.0.option("label", label.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the number of the first item, requires `enumitem`.
+    pub fn start(mut self, start: usize) -> Self {
+        self.0.option("start", start.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, Text};
+
+    #[test]
+    fn render() {
+        let list = Itemize::new().with(Text("one")).item(
+            Item::new()
+                .label("--")
+                .with(Text("two"))
+                .with(Enumerate::new().start(3).label("(\\alph*)").with(Text("a"))),
+        );
+
+        assert_eq!(
+            "\\begin{itemize}\n\\item one\n\\item[--] two\\begin{enumerate}[start={3},label={(\\alph*)}]\n\\item a\n\\end{enumerate}\n\\end{itemize}",
+            list.render()
+        );
+
+        let description =
+            Description::new().item(Item::new().label("Rust").with(Text("a language")));
+        assert_eq!(
+            "\\begin{description}\n\\item[Rust] a language\n\\end{description}",
+            description.render()
+        );
+    }
+
+    #[test]
+    fn requires_enumitem() {
+        let doc = Document::new().with(Enumerate::new().start(2).with(Text("x")));
+        assert!(doc
+            .try_render()
+            .unwrap()
+            .starts_with("\\documentclass{article}\n\\usepackage{enumitem}\n"));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        fn nested<'a>(depth: usize) -> Itemize<'a> {
+            let list = Itemize::new().with(Text("x"));
+            if depth == 1 {
+                list
+            } else {
+                list.with(nested(depth - 1))
+            }
+        }
+
+        assert!(Document::new().with(nested(4)).try_render().is_ok());
+        assert_eq!(
+            Err(Error::TooDeeplyNested {
+                environment: "itemize".to_owned(),
+                depth: 5
+            }),
+            Document::new().with(nested(5)).try_render()
+        );
+
+        // different kinds of lists are counted separately up to 6 levels
+        let mixed = Enumerate::new().with(Enumerate::new().with(nested(4)));
+        assert!(Document::new().with(mixed).try_render().is_ok());
+        let mixed = Enumerate::new().with(Enumerate::new().with(Enumerate::new().with(nested(4))));
+        assert!(Document::new().with(mixed).try_render().is_err());
+    }
+}
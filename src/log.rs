@@ -0,0 +1,559 @@
+//! Reading `.log` files of TeX engines.
+//!
+//! ```
+//! use trylatex::log::{Kind, Report};
+//!
+//! let log = "(./document.tex
+//! LaTeX Warning: Reference `fig:a' on page 1 undefined on input line 12.
+//! )";
+//!
+//! let report = Report::parse(log);
+//! assert_eq!(Kind::UndefinedReference("fig:a".to_owned()), report.diagnostics[0].kind);
+//! assert_eq!(Some(12), report.diagnostics[0].line);
+//! ```
+
+use std::fmt;
+
+/// Engines wrap lines of a log at this length.
+const MAX_PRINT_LINE: usize = 79;
+
+/// Hints that the engine must be run once more.
+const RERUN: &[&str] = &[
+    "Rerun to get",
+    "Label(s) may have changed",
+    "Please rerun LaTeX",
+    "Please (re)run Biber",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoxKind {
+    Hbox,
+    Vbox,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Error,
+    /// A package, class or any other file which isn't found.
+    MissingFile(String),
+    UndefinedReference(String),
+    UndefinedCitation(String),
+    /// By how many points the box is too wide or too high.
+    OverfullBox {
+        kind: BoxKind,
+        by: f64,
+    },
+    UnderfullBox {
+        kind: BoxKind,
+        badness: u32,
+    },
+    /// Cross-references or the bibliography aren't settled yet.
+    Rerun,
+    Warning,
+}
+
+/// A message found in a log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub kind: Kind,
+    pub message: String,
+    /// The file the engine was reading, if it's known.
+    pub file: Option<String>,
+    /// For boxes it's the first line of a paragraph.
+    pub line: Option<usize>,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match self.kind {
+            Kind::Error | Kind::MissingFile(_) => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+
+    /// Whether it's the same message regardless of where it is,
+    /// as lines move when a document is changed.
+    fn same(&self, other: &Diagnostic) -> bool {
+        let message = |d: &Diagnostic| match d.message.rsplit_once(" on input line ") {
+            Some((message, _)) => message.to_owned(),
+            None => d.message.clone(),
+        };
+
+        self.kind == other.kind && self.file == other.file && message(self) == message(other)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: ", file, line)?,
+            (Some(file), None) => write!(f, "{}: ", file)?,
+            (None, Some(line)) => write!(f, "line {}: ", line)?,
+            (None, None) => {}
+        }
+
+        match self.severity() {
+            Severity::Error => write!(f, "error: {}", self.message),
+            Severity::Warning => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+/// Diagnostics of a log in the order they're met.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    pub fn parse(log: &str) -> Self {
+        let lines = unwrap(log);
+        let mut report = Self::default();
+        let mut files = Files::default();
+
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i].as_str();
+            i += 1;
+
+            if let Some(message) = line.strip_prefix("! ") {
+                let (line, skip) = source_line(&lines[i..]);
+                i += skip;
+                report.push(error(message), files.current(), line);
+            } else if let Some((file, line, message)) = file_line_error(line) {
+                let (_, skip) = source_line(&lines[i..]);
+                i += skip;
+                report.push(error(message), Some(file.to_owned()), Some(line));
+            } else if let Some(message) = line.strip_prefix("LaTeX Warning: ") {
+                let mut message = message.to_owned();
+                i += continuation(&lines[i..], "", &mut message);
+                let line = input_line(&message);
+                report.push(warning(&message), files.current(), line);
+            } else if let Some((from, message)) = package_warning(line) {
+                let mut message = message.to_owned();
+                i += continuation(&lines[i..], &format!("({})", from), &mut message);
+                let line = input_line(&message);
+                report.push(warning(&message), files.current(), line);
+            } else if let Some(diagnostic) = bad_box(line) {
+                // an hbox is followed by its content up to a blank line
+                if line.contains("\\hbox") && !line.contains("while \\output is active") {
+                    while i < lines.len() && !lines[i].trim().is_empty() {
+                        i += 1;
+                    }
+                }
+                let (kind, line) = diagnostic;
+                report.push(kind, files.current(), line);
+            } else {
+                files.scan(line);
+            }
+        }
+
+        report
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity() == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity() == Severity::Warning)
+    }
+
+    pub fn needs_rerun(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.kind == Kind::Rerun)
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Diagnostics which aren't in an earlier report, e.g. the one of a main branch.
+    ///
+    /// Lines aren't compared, so moving a paragraph doesn't make its warnings new.
+    pub fn new_since(&self, baseline: &Report) -> Vec<&Diagnostic> {
+        let mut known: Vec<&Diagnostic> = baseline.diagnostics.iter().collect();
+        let mut new = Vec::new();
+        for diagnostic in &self.diagnostics {
+            match known.iter().position(|d| d.same(diagnostic)) {
+                Some(i) => {
+                    known.remove(i);
+                }
+                None => new.push(diagnostic),
+            }
+        }
+
+        new
+    }
+
+    fn push(&mut self, (kind, message): (Kind, String), file: Option<String>, line: Option<usize>) {
+        self.diagnostics.push(Diagnostic {
+            kind,
+            message,
+            file,
+            line,
+        });
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{}", diagnostic)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Joins lines which the engine wrapped.
+fn unwrap(log: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for line in log.lines() {
+        current.push_str(line);
+        if line.chars().count() != MAX_PRINT_LINE {
+            lines.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Files the engine opens with `(` and closes with `)`.
+#[derive(Default)]
+struct Files {
+    stack: Vec<Option<String>>,
+}
+
+impl Files {
+    fn scan(&mut self, line: &str) {
+        let mut chars = line.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '(' => {
+                    let rest = &line[i + 1..];
+                    let end = rest
+                        .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                        .unwrap_or(rest.len());
+                    let name = &rest[..end];
+                    if is_file(name) {
+                        self.stack.push(Some(name.to_owned()));
+                        while chars.peek().is_some_and(|&(j, _)| j <= i + end) {
+                            chars.next();
+                        }
+                    } else {
+                        self.stack.push(None);
+                    }
+                }
+                ')' => {
+                    self.stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn current(&self) -> Option<String> {
+        self.stack.iter().rev().flatten().next().cloned()
+    }
+}
+
+fn is_file(name: &str) -> bool {
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext)
+        .filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    extension.is_some() && (name.contains('/') || !name.starts_with('.'))
+}
+
+/// Finds `l.12 ...` after an error and says how many lines it takes.
+fn source_line(lines: &[String]) -> (Option<usize>, usize) {
+    for (i, line) in lines.iter().take(12).enumerate() {
+        if let Some(rest) = line.strip_prefix("l.") {
+            let number = rest.split(' ').next().and_then(|n| n.parse().ok());
+            // the context is printed on two lines
+            let skip = if i + 1 < lines.len() { i + 2 } else { i + 1 };
+            return (number, skip);
+        }
+    }
+
+    (None, 0)
+}
+
+/// `./document.tex:12: Undefined control sequence.`, printed with `-file-line-error`.
+fn file_line_error(line: &str) -> Option<(&str, usize, &str)> {
+    let mut parts = line.splitn(3, ':');
+    let file = parts.next()?;
+    let number = parts.next()?.parse().ok()?;
+    let message = parts.next()?.strip_prefix(' ')?;
+
+    if is_file(file) {
+        Some((file, number, message))
+    } else {
+        None
+    }
+}
+
+/// `Package natbib Warning: ...` or `Class article Warning: ...`
+fn package_warning(line: &str) -> Option<(&str, &str)> {
+    let rest = line
+        .strip_prefix("Package ")
+        .or_else(|| line.strip_prefix("Class "))?;
+    let (from, message) = rest.split_once(" Warning: ")?;
+    if from.contains(' ') {
+        return None;
+    }
+
+    Some((from, message))
+}
+
+/// Appends lines which continue a warning and says how many there are.
+///
+/// A package prefixes them with `(name)`, LaTeX with nothing
+/// and then they end with a blank line.
+fn continuation(lines: &[String], prefix: &str, message: &mut String) -> usize {
+    let mut count = 0;
+    for line in lines {
+        let rest = match prefix {
+            "" if message.ends_with('.') || line.trim().is_empty() => break,
+            "" => line.as_str(),
+            _ => match line.strip_prefix(prefix) {
+                Some(rest) => rest,
+                None => break,
+            },
+        };
+
+        message.push(' ');
+        message.push_str(rest.trim());
+        count += 1;
+    }
+
+    count
+}
+
+fn input_line(message: &str) -> Option<usize> {
+    let (_, rest) = message.rsplit_once("on input line ")?;
+    rest.trim_end_matches('.').parse().ok()
+}
+
+fn error(message: &str) -> (Kind, String) {
+    let missing = message
+        .strip_prefix("LaTeX Error: File `")
+        .and_then(|rest| rest.split_once("' not found"))
+        .map(|(name, _)| name.to_owned());
+
+    let kind = match missing {
+        Some(name) => Kind::MissingFile(name),
+        None => Kind::Error,
+    };
+
+    (kind, message.to_owned())
+}
+
+fn warning(message: &str) -> (Kind, String) {
+    let quoted = |prefix: &str| {
+        let rest = message.strip_prefix(prefix)?;
+        let rest = rest.strip_prefix('`').or_else(|| rest.strip_prefix('\''))?;
+        let (key, rest) = rest.split_once('\'')?;
+        if rest.contains("undefined") {
+            Some(key.to_owned())
+        } else {
+            None
+        }
+    };
+
+    let kind = if let Some(key) = quoted("Reference ") {
+        Kind::UndefinedReference(key)
+    } else if let Some(key) = quoted("Citation ") {
+        Kind::UndefinedCitation(key)
+    } else if RERUN.iter().any(|hint| message.contains(hint)) {
+        Kind::Rerun
+    } else {
+        Kind::Warning
+    };
+
+    (kind, message.to_owned())
+}
+
+/// `Overfull \hbox (1.5pt too wide) in paragraph at lines 10--12`
+/// or `Underfull \vbox (badness 10000) has occurred while \output is active`.
+fn bad_box(line: &str) -> Option<((Kind, String), Option<usize>)> {
+    let (overfull, rest) = match line.strip_prefix("Overfull \\") {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix("Underfull \\")?),
+    };
+    let kind = match rest.get(..4)? {
+        "hbox" => BoxKind::Hbox,
+        "vbox" => BoxKind::Vbox,
+        _ => return None,
+    };
+    let (measure, rest) = rest[4..].trim_start().strip_prefix('(')?.split_once(')')?;
+
+    let kind = if overfull {
+        let by = measure.split("pt").next()?.parse().ok()?;
+        Kind::OverfullBox { kind, by }
+    } else {
+        let badness = measure.strip_prefix("badness ")?.parse().ok()?;
+        Kind::UnderfullBox { kind, badness }
+    };
+
+    let line_number = rest
+        .rsplit_once("lines ")
+        .or_else(|| rest.rsplit_once("line "))
+        .and_then(|(_, n)| n.split("--").next())
+        .and_then(|n| n.trim().parse().ok());
+
+    Some(((kind, line.to_owned()), line_number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = r"This is pdfTeX, Version 3.141592653-2.6-1.40.25 (TeX Live 2023) (preloaded format=pdflatex)
+entering extended mode
+(./document.tex
+LaTeX2e <2022-11-01> patch level 1
+(/usr/share/texlive/texmf-dist/tex/latex/base/article.cls
+Document Class: article 2022/07/02 v1.4n Standard LaTeX document class
+(/usr/share/texlive/texmf-dist/tex/latex/base/size10.clo))
+(/usr/share/texlive/texmf-dist/tex/latex/natbib/natbib.sty)
+No file document.aux.
+
+Package natbib Warning: Citation `knuth' on page 1 undefined on input line 9.
+
+
+LaTeX Warning: Reference `sec:intro' on page 1 undefined on input line 10.
+
+(./chapter.tex
+Overfull \hbox (12.34567pt too wide) in paragraph at lines 3--5
+[]\OT1/cmr/m/n/10 A very (long) line
+ []
+
+)
+Underfull \vbox (badness 10000) has occurred while \output is active []
+
+! LaTeX Error: File `missing.sty' not found.
+
+Type X to quit or <RETURN> to proceed,
+or enter new name. (Default extension: sty)
+
+Enter file name:
+! Emergency stop.
+<read *>
+
+l.14 \usepackage
+                {missing}
+./document.tex:20: Undefined control sequence.
+l.20 \foo
+
+LaTeX Warning: There were undefined references.
+LaTeX Warning: Label(s) may have changed. Rerun to get cross-references right.
+)";
+
+    #[test]
+    fn diagnostics() {
+        let report = Report::parse(LOG);
+        let found: Vec<_> = report
+            .diagnostics
+            .iter()
+            .map(|d| (d.kind.clone(), d.file.as_deref(), d.line))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (
+                    Kind::UndefinedCitation("knuth".to_owned()),
+                    Some("./document.tex"),
+                    Some(9)
+                ),
+                (
+                    Kind::UndefinedReference("sec:intro".to_owned()),
+                    Some("./document.tex"),
+                    Some(10)
+                ),
+                (
+                    Kind::OverfullBox {
+                        kind: BoxKind::Hbox,
+                        by: 12.34567
+                    },
+                    Some("./chapter.tex"),
+                    Some(3)
+                ),
+                (
+                    Kind::UnderfullBox {
+                        kind: BoxKind::Vbox,
+                        badness: 10000
+                    },
+                    Some("./document.tex"),
+                    None
+                ),
+                (
+                    Kind::MissingFile("missing.sty".to_owned()),
+                    Some("./document.tex"),
+                    Some(14)
+                ),
+                (Kind::Error, Some("./document.tex"), Some(20)),
+                (Kind::Warning, Some("./document.tex"), None),
+                (Kind::Rerun, Some("./document.tex"), None),
+            ],
+            found
+        );
+        assert_eq!(2, report.errors().count());
+        assert!(report.needs_rerun());
+        assert_eq!(
+            "./document.tex:20: error: Undefined control sequence.",
+            report.diagnostics[5].to_string()
+        );
+    }
+
+    #[test]
+    fn wrapped_lines_and_continuations() {
+        let warning = "LaTeX Warning: Reference `a-label-with-a-rather-long-name' on page 1 undefined on input line 8.";
+        let log = format!(
+            "(./document.tex\nPackage hyperref Warning: Token not allowed in a PDF string\n(hyperref)                removing `math shift' on input line 4.\n\n{}\n{}\n)",
+            &warning[..MAX_PRINT_LINE],
+            &warning[MAX_PRINT_LINE..],
+        );
+
+        let report = Report::parse(&log);
+        assert_eq!(
+            "Token not allowed in a PDF string removing `math shift' on input line 4.",
+            report.diagnostics[0].message
+        );
+        assert_eq!(Some(4), report.diagnostics[0].line);
+        assert_eq!(
+            Kind::UndefinedReference("a-label-with-a-rather-long-name".to_owned()),
+            report.diagnostics[1].kind
+        );
+        assert_eq!(Some(8), report.diagnostics[1].line);
+    }
+
+    #[test]
+    fn new_since() {
+        let old = Report::parse(
+            "(./document.tex\nLaTeX Warning: Reference `a' on page 1 undefined on input line 3.\n)",
+        );
+        let new = Report::parse(
+            "(./document.tex\nLaTeX Warning: Reference `a' on page 1 undefined on input line 4.\nLaTeX Warning: Reference `b' on page 1 undefined on input line 5.\n)",
+        );
+
+        let found: Vec<_> = new.new_since(&old).into_iter().map(|d| &d.kind).collect();
+        assert_eq!(vec![&Kind::UndefinedReference("b".to_owned())], found);
+        assert!(old.new_since(&new).is_empty());
+    }
+}
@@ -0,0 +1,572 @@
+//! Reading Markdown into elements, requires the `markdown` feature.
+//!
+//! CommonMark is supported along with tables and strikethrough
+//! of GitHub Flavored Markdown. Raw HTML is dropped.
+//!
+//! ```
+//! use trylatex::{markdown, Container, Document, Element};
+//!
+//! let doc = Document::new().with(markdown::convert("# Results\n\nIt *works*, 100%."));
+//! assert!(doc
+//!     .render()
+//!     .contains("\\section{Results}\nIt \\emph{works}, 100\\%."));
+//! ```
+
+use pulldown_cmark::{Alignment, Event, HeadingLevel, LinkType, Options, Parser, Tag};
+use std::{fmt, iter::Peekable, mem};
+
+use crate::node::{FloatKind, Level, ListItem, ListKind, Node, TableCell, TableRow};
+use crate::{Column, Context, Element, Error, Macros, Package, Parameter, Raw};
+
+const LEVELS: [Level; 7] = [
+    Level::Part,
+    Level::Chapter,
+    Level::Section,
+    Level::Subsection,
+    Level::Subsubsection,
+    Level::Paragraph,
+    Level::Subparagraph,
+];
+
+/// Converts Markdown with `#` headings as sections.
+pub fn convert<S: AsRef<str>>(src: S) -> Markdown {
+    Converter::new().convert(src)
+}
+
+pub struct Converter {
+    top: Level,
+}
+
+impl Converter {
+    pub fn new() -> Self {
+        Self {
+            top: Level::Section,
+        }
+    }
+
+    /// Sets the level of `#` headings, `##` go one level below and so on.
+    ///
+    /// Headings below [`Level::Subparagraph`] stay subparagraphs.
+    pub fn top_level(mut self, level: Level) -> Self {
+        self.top = level;
+        self
+    }
+
+    pub fn convert<S: AsRef<str>>(&self, src: S) -> Markdown {
+        let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+        let mut state = State {
+            events: Parser::new_ext(src.as_ref(), options).peekable(),
+            top: LEVELS.iter().position(|l| *l == self.top).unwrap_or(2),
+            packages: Vec::new(),
+        };
+
+        let blocks = state.blocks();
+        Markdown {
+            nodes: nest(blocks),
+            packages: state.packages,
+        }
+    }
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Converted Markdown, headings hold the content up to the next heading
+/// of the same or a higher level.
+pub struct Markdown {
+    nodes: Vec<Node>,
+    packages: Vec<Package>,
+}
+
+impl Markdown {
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn into_nodes(self) -> Vec<Node> {
+        self.nodes
+    }
+}
+
+impl Element for Markdown {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.nodes.iter().try_for_each(|node| node.render_to(w))
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        for package in &self.packages {
+            ctx.require(package.clone())?;
+        }
+
+        self.nodes.iter().try_for_each(|node| node.visit(ctx))
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Sequence {
+            body: self.nodes.clone(),
+        }
+    }
+}
+
+struct State<'s> {
+    events: Peekable<Parser<'s>>,
+    /// The index of the level of `#` headings.
+    top: usize,
+    packages: Vec<Package>,
+}
+
+impl<'s> State<'s> {
+    /// Reads blocks up to the end of the current one.
+    fn blocks(&mut self) -> Vec<Node> {
+        let mut blocks = Vec::new();
+        // items of tight lists have text without a paragraph
+        let mut text = Vec::new();
+        while let Some(event) = self.events.next() {
+            match event {
+                Event::End(_) => break,
+                Event::Start(tag) if is_block(&tag) => {
+                    if !text.is_empty() {
+                        blocks.push(sequence(mem::take(&mut text)));
+                    }
+                    blocks.push(self.start(tag));
+                }
+                Event::Rule => {
+                    if !text.is_empty() {
+                        blocks.push(sequence(mem::take(&mut text)));
+                    }
+                    blocks.push(rule());
+                }
+                event => text.extend(self.event(event)),
+            }
+        }
+        if !text.is_empty() {
+            blocks.push(sequence(text));
+        }
+
+        blocks
+    }
+
+    /// Reads inline content up to the end of the current element.
+    fn inlines(&mut self) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        while let Some(event) = self.events.next() {
+            match event {
+                Event::End(_) => break,
+                event => nodes.extend(self.event(event)),
+            }
+        }
+
+        nodes
+    }
+
+    fn event(&mut self, event: Event<'s>) -> Option<Node> {
+        match event {
+            Event::Start(tag) => Some(self.start(tag)),
+            Event::Text(text) => Some(Node::Text {
+                text: text.into_string(),
+            }),
+            Event::Code(code) => Some(command("texttt", vec![text(&code)])),
+            Event::SoftBreak => Some(raw("\n")),
+            Event::HardBreak => Some(raw("\\\\\n")),
+            Event::Rule => Some(rule()),
+            // HTML means nothing to LaTeX, the rest isn't enabled
+            _ => None,
+        }
+    }
+
+    fn start(&mut self, tag: Tag<'s>) -> Node {
+        match tag {
+            Tag::Paragraph => self.paragraph(),
+            Tag::Heading { level, .. } => Node::Heading {
+                level: self.level(level),
+                title: parameter(self.inlines()),
+                short: None,
+                star: false,
+                label: None,
+                body: Vec::new(),
+            },
+            Tag::BlockQuote(_) => Node::Environment {
+                name: "quote".to_owned(),
+                args: Vec::new(),
+                body: join(self.blocks()),
+            },
+            Tag::CodeBlock(_) => {
+                let mut code = String::new();
+                while let Some(Event::Text(text)) = self.events.next() {
+                    code.push_str(&text);
+                }
+                if code.ends_with('\n') {
+                    code.pop();
+                }
+
+                Node::Environment {
+                    name: "verbatim".to_owned(),
+                    args: Vec::new(),
+                    body: vec![raw(code)],
+                }
+            }
+            Tag::List(start) => {
+                let mut items = Vec::new();
+                while let Some(Event::Start(Tag::Item)) = self.events.next() {
+                    items.push(ListItem {
+                        label: None,
+                        overlay: None,
+                        body: join(self.blocks()),
+                    });
+                }
+
+                Node::List {
+                    kind: match start {
+                        Some(_) => ListKind::Enumerate,
+                        None => ListKind::Itemize,
+                    },
+                    label: None,
+                    start: start.filter(|n| *n != 1).map(|n| n as usize),
+                    items,
+                }
+            }
+            Tag::Table(alignments) => self.table(alignments),
+            Tag::Emphasis => command("emph", self.inlines()),
+            Tag::Strong => command("textbf", self.inlines()),
+            Tag::Strikethrough => {
+                // `ulem` underlines `\emph` unless told not to
+                self.require(Package::new("ulem").option("normalem"));
+                command("sout", self.inlines())
+            }
+            Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            } => {
+                self.require(Package::new("hyperref"));
+                let content = self.inlines();
+                match link_type {
+                    LinkType::Autolink => {
+                        Node::Macros(Macros::new("url").param(Raw(url(&dest_url))))
+                    }
+                    LinkType::Email => Node::Macros(
+                        Macros::new("href")
+                            .param(Raw(format!("mailto:{}", url(&dest_url))))
+                            .param(parameter(content)),
+                    ),
+                    _ => Node::Macros(
+                        Macros::new("href")
+                            .param(Raw(url(&dest_url)))
+                            .param(parameter(content)),
+                    ),
+                }
+            }
+            Tag::Image { dest_url, .. } => {
+                // the description can't be shown next to the image
+                self.inlines();
+                graphics(dest_url.into_string())
+            }
+            _ => sequence(self.inlines()),
+        }
+    }
+
+    /// A paragraph of a single image is a figure, the description is its caption.
+    fn paragraph(&mut self) -> Node {
+        let path = match self.events.peek() {
+            Some(Event::Start(Tag::Image { dest_url, .. })) => dest_url.to_string(),
+            _ => return sequence(self.inlines()),
+        };
+
+        self.events.next();
+        let description = self.inlines();
+        if let Some(Event::End(_)) = self.events.peek() {
+            self.events.next();
+            return Node::Float {
+                kind: FloatKind::Figure,
+                placement: Vec::new(),
+                caption: Some(description).filter(|d| !d.is_empty()).map(parameter),
+                short: None,
+                label: None,
+                body: vec![graphics(path)],
+            };
+        }
+
+        let mut nodes = vec![graphics(path)];
+        nodes.extend(self.inlines());
+        sequence(nodes)
+    }
+
+    fn table(&mut self, alignments: Vec<Alignment>) -> Node {
+        let columns = alignments
+            .iter()
+            .map(|alignment| match alignment {
+                Alignment::Center => Column::Center,
+                Alignment::Right => Column::Right,
+                Alignment::Left | Alignment::None => Column::Left,
+            })
+            .collect();
+
+        let mut rows = vec![TableRow::Toprule];
+        // the head and then rows, up to the end of the table
+        while let Some(Event::Start(tag)) = self.events.next() {
+            let mut cells = Vec::new();
+            while let Some(Event::Start(Tag::TableCell)) = self.events.next() {
+                cells.push(TableCell {
+                    columns: None,
+                    spec: Vec::new(),
+                    rows: None,
+                    body: self.inlines(),
+                });
+            }
+
+            rows.push(TableRow::Row { cells });
+            if let Tag::TableHead = tag {
+                rows.push(TableRow::Midrule);
+            }
+        }
+        rows.push(TableRow::Bottomrule);
+
+        Node::Environment {
+            name: "center".to_owned(),
+            args: Vec::new(),
+            body: vec![Node::Tabular { columns, rows }],
+        }
+    }
+
+    fn level(&self, level: HeadingLevel) -> Level {
+        let i = self.top + level as usize - 1;
+        LEVELS[i.min(LEVELS.len() - 1)]
+    }
+
+    fn require(&mut self, package: Package) {
+        if !self.packages.contains(&package) {
+            self.packages.push(package);
+        }
+    }
+}
+
+fn is_block(tag: &Tag) -> bool {
+    matches!(
+        tag,
+        Tag::Paragraph
+            | Tag::Heading { .. }
+            | Tag::BlockQuote(_)
+            | Tag::CodeBlock(_)
+            | Tag::HtmlBlock
+            | Tag::List(_)
+            | Tag::Table(_)
+    )
+}
+
+/// Puts blocks after a heading into it, up to a heading
+/// of the same or a higher level.
+fn nest(blocks: Vec<Node>) -> Vec<Node> {
+    fn close(root: &mut Vec<Node>, open: &mut Vec<Node>) {
+        let mut heading = open.pop().expect("a heading is open");
+        if let Node::Heading { body, .. } = &mut heading {
+            *body = join(mem::take(body));
+        }
+
+        match open.last_mut() {
+            Some(Node::Heading { body, .. }) => body.push(heading),
+            _ => root.push(heading),
+        }
+    }
+
+    let mut root = Vec::new();
+    let mut open = Vec::new();
+    for block in blocks {
+        if let Node::Heading { level, .. } = block {
+            while matches!(open.last(), Some(Node::Heading { level: l, .. }) if *l >= level) {
+                close(&mut root, &mut open);
+            }
+            open.push(block);
+            continue;
+        }
+
+        match open.last_mut() {
+            Some(Node::Heading { body, .. }) => body.push(block),
+            _ => root.push(block),
+        }
+    }
+    while !open.is_empty() {
+        close(&mut root, &mut open);
+    }
+
+    join(root)
+}
+
+/// Separates blocks by an empty line.
+fn join(blocks: Vec<Node>) -> Vec<Node> {
+    let mut nodes = Vec::with_capacity(blocks.len() * 2);
+    for block in blocks {
+        if !nodes.is_empty() {
+            nodes.push(raw("\n\n"));
+        }
+        nodes.push(block);
+    }
+
+    nodes
+}
+
+fn sequence(mut nodes: Vec<Node>) -> Node {
+    match nodes.len() {
+        1 => nodes.remove(0),
+        _ => Node::Sequence { body: nodes },
+    }
+}
+
+fn parameter(mut nodes: Vec<Node>) -> Parameter {
+    match nodes.as_slice() {
+        [Node::Text { .. }] => match nodes.remove(0) {
+            Node::Text { text } => Parameter::Literal(text),
+            _ => unreachable!(),
+        },
+        _ => Parameter::Raw(nodes.iter().map(Element::render).collect()),
+    }
+}
+
+fn command(name: &str, content: Vec<Node>) -> Node {
+    Node::Macros(Macros::new(name).param(parameter(content)))
+}
+
+fn graphics(path: String) -> Node {
+    Node::Graphics {
+        path,
+        width: None,
+        height: None,
+        scale: None,
+        angle: None,
+        trim: None,
+        clip: false,
+    }
+}
+
+fn text(s: &str) -> Node {
+    Node::Text { text: s.to_owned() }
+}
+
+fn raw<S: Into<String>>(latex: S) -> Node {
+    Node::Raw {
+        latex: latex.into(),
+    }
+}
+
+fn rule() -> Node {
+    raw("\\noindent\\rule{\\linewidth}{0.4pt}")
+}
+
+/// Escapes characters which `\url` and `\href` don't take as is.
+fn url(url: &str) -> String {
+    url.replace('%', "\\%").replace('#', "\\#")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Container, Document, DocumentType};
+
+    #[test]
+    fn blocks() {
+        let src = "# Intro
+
+Some *emphasis* and `a_b`.
+
+## Details
+
+- one
+- two
+  lines
+
+3. three
+4. four
+
+> quoted
+
+```rust
+fn main() {}
+```
+
+---
+
+# Data
+
+| Name | Value |
+|:-----|------:|
+| a    | 1     |
+";
+
+        let doc = Document::new().with(convert(src));
+        assert_eq!(
+            r"\documentclass{article}
+\usepackage{enumitem}
+\usepackage{booktabs}
+
+\begin{document}
+\section{Intro}
+Some \emph{emphasis} and \texttt{a\_b}.
+
+\subsection{Details}
+\begin{itemize}
+\item one
+\item two
+lines
+\end{itemize}
+
+\begin{enumerate}[start={3}]
+\item three
+\item four
+\end{enumerate}
+
+\begin{quote}
+quoted
+\end{quote}
+
+\begin{verbatim}
+fn main() {}
+\end{verbatim}
+
+\noindent\rule{\linewidth}{0.4pt}
+
+\section{Data}
+\begin{center}
+\begin{tabular}{lr}
+\toprule
+Name & Value \\
+\midrule
+a & 1 \\
+\bottomrule
+\end{tabular}
+\end{center}
+\end{document}
+",
+            doc.try_render().unwrap()
+        );
+    }
+
+    #[test]
+    fn inlines() {
+        let src = "See [the **docs**](https://example.com/a%20b#top), <https://rust-lang.org> and ~~old~~.
+
+![A plot](plot.png)";
+
+        let mut doc = Document::new();
+        doc.preambule().r#type(DocumentType::Report);
+        let doc = doc.with(Converter::new().top_level(Level::Chapter).convert(src));
+
+        let rendered = doc.try_render().unwrap();
+        assert!(rendered.contains(
+            r"\usepackage{hyperref}
+\usepackage[normalem]{ulem}
+\usepackage{graphicx}"
+        ));
+        assert!(rendered.contains(
+            r"See \href{https://example.com/a\%20b\#top}{the \textbf{docs}}, \url{https://rust-lang.org} and \sout{old}."
+        ));
+        assert!(rendered.contains(
+            r"\begin{figure}
+\centering
+\includegraphics{plot.png}
+\caption{A plot}
+\end{figure}"
+        ));
+    }
+}
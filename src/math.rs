@@ -0,0 +1,349 @@
+//! Math content is LaTeX code, so it's usually given as `Raw`,
+//! e.g. `InlineMath::new(Raw("e^{i\\pi} + 1 = 0"))`.
+
+use crate::node::{flatten, AlignRow, Node};
+use crate::{Container, Context, Element, Error, Label, Macros, Package};
+use std::fmt;
+
+/// Math inside a paragraph, `$..$`.
+pub struct InlineMath<'a>(Box<dyn Element + 'a>);
+
+impl<'a> InlineMath<'a> {
+    pub fn new<E: Element + 'a>(content: E) -> Self {
+        Self(Box::new(content))
+    }
+}
+
+impl Element for InlineMath<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_char('$')?;
+        self.0.render_to(w)?;
+        w.write_char('$')
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        self.0.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        Node::InlineMath {
+            body: flatten(self.0.to_node()),
+        }
+    }
+}
+
+/// Unnumbered math on its own line, `\[..\]`.
+pub struct DisplayMath<'a>(Box<dyn Element + 'a>);
+
+impl<'a> DisplayMath<'a> {
+    pub fn new<E: Element + 'a>(content: E) -> Self {
+        Self(Box::new(content))
+    }
+}
+
+impl Element for DisplayMath<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_str("\\[\n")?;
+        self.0.render_to(w)?;
+        w.write_str("\n\\]")
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        self.0.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        Node::DisplayMath {
+            body: flatten(self.0.to_node()),
+        }
+    }
+}
+
+fn environment(name: &str, starred: bool) -> String {
+    if starred {
+        format!("{}*", name)
+    } else {
+        name.to_owned()
+    }
+}
+
+/// A numbered `equation`.
+pub struct Equation<'a> {
+    content: Box<dyn Element + 'a>,
+    starred: bool,
+    label: Option<Label>,
+}
+
+impl<'a> Equation<'a> {
+    pub fn new<E: Element + 'a>(content: E) -> Self {
+        Self {
+            content: Box::new(content),
+            starred: false,
+            label: None,
+        }
+    }
+
+    /// Makes the equation unnumbered.
+    pub fn star(mut self) -> Self {
+        self.starred = true;
+        self
+    }
+
+    /// Labels the equation, references are made with the returned label.
+    pub fn label<S: AsRef<str>>(mut self, key: S) -> (Self, Label) {
+        let label = Label::new(key);
+        self.label = Some(label.clone());
+        (self, label)
+    }
+}
+
+impl Element for Equation<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let name = environment("equation", self.starred);
+
+        Macros::new("begin").param(&name).render_to(w)?;
+        w.write_char('\n')?;
+        self.content.render_to(w)?;
+        if let Some(label) = &self.label {
+            label.render_to(w)?;
+        }
+        w.write_char('\n')?;
+        Macros::new("end").param(&name).render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        ctx.require(Package::new("amsmath"))?;
+        if let Some(label) = &self.label {
+            label.visit(ctx)?;
+        }
+
+        self.content.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Equation {
+            body: flatten(self.content.to_node()),
+            star: self.starred,
+            label: self.label.clone(),
+        }
+    }
+}
+
+/// A line of an [`Align`].
+///
+/// Each element added with [`Container::with`] starts at the next
+/// alignment point `&`.
+pub struct AlignLine<'a> {
+    cells: Vec<Box<dyn Element + 'a>>,
+    label: Option<Label>,
+    notag: bool,
+}
+
+impl AlignLine<'_> {
+    pub fn new() -> Self {
+        Self {
+            cells: Vec::new(),
+            label: None,
+            notag: false,
+        }
+    }
+
+    /// Labels the line, references are made with the returned label.
+    pub fn label<S: AsRef<str>>(mut self, key: S) -> (Self, Label) {
+        let label = Label::new(key);
+        self.label = Some(label.clone());
+        (self, label)
+    }
+
+    /// Leaves the line unnumbered.
+    pub fn notag(mut self) -> Self {
+        self.notag = true;
+        self
+    }
+
+    fn to_row(&self) -> AlignRow {
+        AlignRow {
+            cells: self.cells.iter().map(|c| flatten(c.to_node())).collect(),
+            label: self.label.clone(),
+            notag: self.notag,
+        }
+    }
+}
+
+impl Default for AlignLine<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for AlignLine<'a> {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.cells.push(Box::new(e));
+        self
+    }
+}
+
+impl Element for AlignLine<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        for (i, cell) in self.cells.iter().enumerate() {
+            if i > 0 {
+                w.write_str(" & ")?;
+            }
+            cell.render_to(w)?;
+        }
+        if self.notag {
+            w.write_char(' ')?;
+            Macros::new("notag").render_to(w)?;
+        }
+        if let Some(label) = &self.label {
+            w.write_char(' ')?;
+            label.render_to(w)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        if let Some(label) = &self.label {
+            label.visit(ctx)?;
+        }
+
+        self.cells.iter().try_for_each(|c| c.visit(ctx))
+    }
+}
+
+/// Several numbered lines aligned at `&`, each element added with
+/// [`Container::with`] is a line.
+pub struct Align<'a> {
+    lines: Vec<AlignLine<'a>>,
+    starred: bool,
+}
+
+impl<'a> Align<'a> {
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            starred: false,
+        }
+    }
+
+    pub fn line(mut self, line: AlignLine<'a>) -> Self {
+        self.lines.push(line);
+        self
+    }
+
+    /// Makes every line unnumbered.
+    pub fn star(mut self) -> Self {
+        self.starred = true;
+        self
+    }
+}
+
+impl Default for Align<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for Align<'a> {
+    fn with<E: Element + 'a>(self, e: E) -> Self {
+        self.line(AlignLine::new().with(e))
+    }
+}
+
+impl Element for Align<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let name = environment("align", self.starred);
+
+        Macros::new("begin").param(&name).render_to(w)?;
+        w.write_char('\n')?;
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                w.write_str(" \\\\\n")?;
+            }
+            line.render_to(w)?;
+        }
+        w.write_char('\n')?;
+        Macros::new("end").param(&name).render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        ctx.require(Package::new("amsmath"))?;
+        self.lines.iter().try_for_each(|l| l.visit(ctx))
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Align {
+            lines: self.lines.iter().map(AlignLine::to_row).collect(),
+            star: self.starred,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, Raw, Text};
+
+    #[test]
+    fn render() {
+        let doc = Document::new()
+            .with(Text("Euler: "))
+            .with(InlineMath::new(Raw("e^{i\\pi} + 1 = 0")))
+            .with(DisplayMath::new(Raw("x_1")))
+            .with(
+                Equation::new(Raw("a^2 + b^2 = c^2"))
+                    .label("eq:pythagoras")
+                    .0,
+            )
+            .with(
+                Align::new()
+                    .line(
+                        AlignLine::new()
+                            .with(Raw("f(x)"))
+                            .with(Raw("= x^2"))
+                            .label("eq:f")
+                            .0,
+                    )
+                    .line(
+                        AlignLine::new()
+                            .with(Raw(""))
+                            .with(Raw("= x \\cdot x"))
+                            .notag(),
+                    ),
+            );
+
+        assert_eq!(
+            "\\documentclass{article}
+\\usepackage{amsmath}
+
+\\begin{document}
+Euler: $e^{i\\pi} + 1 = 0$\\[
+x_1
+\\]\\begin{equation}
+a^2 + b^2 = c^2\\label{eq:pythagoras}
+\\end{equation}\\begin{align}
+f(x) & = x^2 \\label{eq:f} \\\\
+ & = x \\cdot x \\notag
+\\end{align}
+\\end{document}
+",
+            doc.try_render().unwrap()
+        );
+    }
+
+    #[test]
+    fn starred() {
+        assert_eq!(
+            "\\begin{equation*}\nx\n\\end{equation*}",
+            Equation::new(Raw("x")).star().render()
+        );
+        assert_eq!(
+            "\\begin{align*}\na & b\n\\end{align*}",
+            Align::new()
+                .star()
+                .line(AlignLine::new().with(Raw("a")).with(Raw("b")))
+                .render()
+        );
+    }
+}
@@ -0,0 +1,699 @@
+//! A closed set of elements, so documents can be stored and built from data.
+//!
+//! Every [`Element`] turns into a [`Node`] by [`Element::to_node`],
+//! elements which have no node of their own are kept as the LaTeX they render.
+//! With the `serde` feature nodes and [`Document`] are serializable:
+//!
+//! ```
+//! # #[cfg(feature = "serde")]
+//! # {
+//! use trylatex::{Document, Element};
+//!
+//! let json = r#"{
+//!     "preambule": { "title": "Report" },
+//!     "body": [
+//!         { "type": "heading", "level": "section", "title": "Intro", "body": [
+//!             { "type": "text", "text": "Hello & welcome" }
+//!         ] }
+//!     ]
+//! }"#;
+//!
+//! let doc: Document = serde_json::from_str(json).unwrap();
+//! assert!(doc.render().contains("\\section{Intro}\nHello \\& welcome"));
+//! # }
+//! ```
+
+use std::fmt;
+
+use crate::beamer::Overlay;
+
+use crate::{
+    Align, AlignLine, Area, Argument, Chapter, Cite, CiteCommand, Column, Container, Context, Cref,
+    Description, DisplayMath, Document, Element, Enumerate, Environment, EqRef, Equation, Error,
+    Figure, Graphics, InlineMath, Item, Itemize, Label, Macros, PageRef, Paragraph, Parameter,
+    Part, Position, Preambule, Raw, Ref, Row, Section, SubFigure, SubFigures, Subparagraph,
+    Subsection, Subsubsection, Table, Tabular, Text,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
+pub enum Node {
+    /// Plain text, escaped on render.
+    Text {
+        text: String,
+    },
+    /// LaTeX code, rendered verbatim.
+    Raw {
+        latex: String,
+    },
+    Macros(Macros),
+    /// Nodes rendered one after another.
+    Sequence {
+        #[cfg_attr(feature = "serde", serde(default))]
+        body: Vec<Node>,
+    },
+    Environment {
+        name: String,
+        #[cfg_attr(feature = "serde", serde(default))]
+        args: Vec<Argument>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        body: Vec<Node>,
+    },
+    Heading {
+        level: Level,
+        title: Parameter,
+        short: Option<Parameter>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        star: bool,
+        label: Option<Label>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        body: Vec<Node>,
+    },
+    List {
+        kind: ListKind,
+        /// The bullet or the format of numbers, see [`Itemize::label`].
+        label: Option<String>,
+        start: Option<usize>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        items: Vec<ListItem>,
+    },
+    Float {
+        kind: FloatKind,
+        #[cfg_attr(feature = "serde", serde(default))]
+        placement: Vec<Position>,
+        caption: Option<Parameter>,
+        short: Option<Parameter>,
+        label: Option<Label>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        body: Vec<Node>,
+    },
+    Tabular {
+        columns: Vec<Column>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        rows: Vec<TableRow>,
+    },
+    Graphics {
+        path: String,
+        width: Option<String>,
+        height: Option<String>,
+        scale: Option<f64>,
+        angle: Option<f64>,
+        /// Left, bottom, right and top.
+        trim: Option<[String; 4]>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        clip: bool,
+    },
+    SubFigure(SubFigureItem),
+    SubFigures {
+        columns: usize,
+        #[cfg_attr(feature = "serde", serde(default))]
+        items: Vec<SubFigureItem>,
+    },
+    InlineMath {
+        #[cfg_attr(feature = "serde", serde(default))]
+        body: Vec<Node>,
+    },
+    DisplayMath {
+        #[cfg_attr(feature = "serde", serde(default))]
+        body: Vec<Node>,
+    },
+    Equation {
+        #[cfg_attr(feature = "serde", serde(default))]
+        body: Vec<Node>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        star: bool,
+        label: Option<Label>,
+    },
+    Align {
+        #[cfg_attr(feature = "serde", serde(default))]
+        lines: Vec<AlignRow>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        star: bool,
+    },
+    Label {
+        key: String,
+    },
+    Reference {
+        kind: RefKind,
+        key: String,
+    },
+    Cite {
+        command: CiteCommand,
+        keys: Vec<String>,
+        note: Option<Parameter>,
+    },
+}
+
+/// The level of a heading, from the highest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Level {
+    Part,
+    Chapter,
+    Section,
+    Subsection,
+    Subsubsection,
+    Paragraph,
+    Subparagraph,
+}
+
+impl Level {
+    pub(crate) fn from_command(command: &str) -> Option<Self> {
+        match command {
+            "part" => Some(Level::Part),
+            "chapter" => Some(Level::Chapter),
+            "section" => Some(Level::Section),
+            "subsection" => Some(Level::Subsection),
+            "subsubsection" => Some(Level::Subsubsection),
+            "paragraph" => Some(Level::Paragraph),
+            "subparagraph" => Some(Level::Subparagraph),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ListKind {
+    Itemize,
+    Enumerate,
+    Description,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListItem {
+    pub label: Option<Parameter>,
+    pub overlay: Option<Overlay>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub body: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum FloatKind {
+    Figure,
+    Table,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
+pub enum TableRow {
+    Row { cells: Vec<TableCell> },
+    Toprule,
+    Midrule,
+    Bottomrule,
+    Hline,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableCell {
+    /// How many columns the cell spans, see [`Cell::columns`](crate::Cell::columns).
+    pub columns: Option<usize>,
+    /// The format of a cell spanning columns.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spec: Vec<Column>,
+    pub rows: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub body: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubFigureItem {
+    pub width: Option<String>,
+    pub caption: Option<Parameter>,
+    pub label: Option<Label>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub body: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlignRow {
+    /// The content between alignment points.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cells: Vec<Vec<Node>>,
+    pub label: Option<Label>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub notag: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum RefKind {
+    Ref,
+    PageRef,
+    EqRef,
+    Cref,
+}
+
+impl Node {
+    /// Builds the element the node stands for.
+    pub fn into_element(self) -> Box<dyn Element> {
+        match self {
+            Node::Text { text } => Box::new(Text(text)),
+            Node::Raw { latex } => Box::new(Raw(latex)),
+            Node::Macros(m) => Box::new(m),
+            Node::Sequence { body } => Box::new(area(body)),
+            Node::Environment { name, args, body } => {
+                let environment =
+                    args.into_iter()
+                        .fold(Environment::new(name), |environment, arg| match arg {
+                            Argument::Mandatory(p) => environment.param(p),
+                            Argument::Optional(p) => environment.opt(p),
+                        });
+                Box::new(with(environment, body))
+            }
+            Node::Heading {
+                level,
+                title,
+                short,
+                star,
+                label,
+                body,
+            } => {
+                macro_rules! heading {
+                    ($heading:ident) => {{
+                        let mut heading = $heading::new(title);
+                        if let Some(short) = short {
+                            heading = heading.short(short);
+                        }
+                        if star {
+                            heading = heading.star();
+                        }
+                        if let Some(label) = label {
+                            heading = heading.label(label.key()).0;
+                        }
+                        Box::new(with(heading, body))
+                    }};
+                }
+
+                match level {
+                    Level::Part => heading!(Part),
+                    Level::Chapter => heading!(Chapter),
+                    Level::Section => heading!(Section),
+                    Level::Subsection => heading!(Subsection),
+                    Level::Subsubsection => heading!(Subsubsection),
+                    Level::Paragraph => heading!(Paragraph),
+                    Level::Subparagraph => heading!(Subparagraph),
+                }
+            }
+            Node::List {
+                kind,
+                label,
+                start,
+                items,
+            } => {
+                let items = items.into_iter().map(|item| {
+                    let mut new = match item.label {
+                        Some(label) => Item::new().label(label),
+                        None => Item::new(),
+                    };
+                    if let Some(overlay) = item.overlay {
+                        new = new.overlay(overlay);
+                    }
+                    with(new, item.body)
+                });
+
+                match kind {
+                    ListKind::Itemize => {
+                        let mut list = Itemize::new();
+                        if let Some(label) = label {
+                            list = list.label(label);
+                        }
+                        Box::new(items.fold(list, Itemize::item))
+                    }
+                    ListKind::Enumerate => {
+                        let mut list = Enumerate::new();
+                        if let Some(label) = label {
+                            list = list.label(label);
+                        }
+                        if let Some(start) = start {
+                            list = list.start(start);
+                        }
+                        Box::new(items.fold(list, Enumerate::item))
+                    }
+                    ListKind::Description => {
+                        Box::new(items.fold(Description::new(), Description::item))
+                    }
+                }
+            }
+            Node::Float {
+                kind,
+                placement,
+                caption,
+                short,
+                label,
+                body,
+            } => {
+                macro_rules! float {
+                    ($float:ident) => {{
+                        let mut float = $float::new().placement(&placement);
+                        if let Some(caption) = caption {
+                            float = float.caption(caption);
+                        }
+                        if let Some(short) = short {
+                            float = float.short(short);
+                        }
+                        if let Some(label) = label {
+                            float = float.label(label.key()).0;
+                        }
+                        Box::new(with(float, body))
+                    }};
+                }
+
+                match kind {
+                    FloatKind::Figure => float!(Figure),
+                    FloatKind::Table => float!(Table),
+                }
+            }
+            Node::Tabular { columns, rows } => {
+                let tabular =
+                    rows.into_iter()
+                        .fold(Tabular::new(columns), |tabular, row| match row {
+                            TableRow::Row { cells } => {
+                                tabular.row(cells.into_iter().fold(Row::new(), |row, cell| {
+                                    let mut c = crate::Cell::new(area(cell.body));
+                                    if let Some(n) = cell.columns {
+                                        c = c.columns(n, cell.spec);
+                                    }
+                                    if let Some(n) = cell.rows {
+                                        c = c.rows(n);
+                                    }
+                                    row.cell(c)
+                                }))
+                            }
+                            TableRow::Toprule => tabular.toprule(),
+                            TableRow::Midrule => tabular.midrule(),
+                            TableRow::Bottomrule => tabular.bottomrule(),
+                            TableRow::Hline => tabular.hline(),
+                        });
+                Box::new(tabular)
+            }
+            Node::Graphics {
+                path,
+                width,
+                height,
+                scale,
+                angle,
+                trim,
+                clip,
+            } => {
+                let mut graphics = Graphics::new(path);
+                if let Some(width) = width {
+                    graphics = graphics.width(width);
+                }
+                if let Some(height) = height {
+                    graphics = graphics.height(height);
+                }
+                if let Some(scale) = scale {
+                    graphics = graphics.scale(scale);
+                }
+                if let Some(angle) = angle {
+                    graphics = graphics.angle(angle);
+                }
+                if let Some([left, bottom, right, top]) = trim {
+                    graphics = graphics.trim(left, bottom, right, top);
+                }
+                if clip {
+                    graphics = graphics.clip();
+                }
+                Box::new(graphics)
+            }
+            Node::SubFigure(item) => Box::new(subfigure(item)),
+            Node::SubFigures { columns, items } => Box::new(
+                items
+                    .into_iter()
+                    .map(subfigure)
+                    .fold(SubFigures::new(columns), SubFigures::subfigure),
+            ),
+            Node::InlineMath { body } => Box::new(InlineMath::new(area(body))),
+            Node::DisplayMath { body } => Box::new(DisplayMath::new(area(body))),
+            Node::Equation { body, star, label } => {
+                let mut equation = Equation::new(area(body));
+                if star {
+                    equation = equation.star();
+                }
+                if let Some(label) = label {
+                    equation = equation.label(label.key()).0;
+                }
+                Box::new(equation)
+            }
+            Node::Align { lines, star } => {
+                let mut align = lines.into_iter().fold(Align::new(), |align, row| {
+                    let mut line = AlignLine::new();
+                    if let Some(label) = row.label {
+                        line = line.label(label.key()).0;
+                    }
+                    if row.notag {
+                        line = line.notag();
+                    }
+                    align.line(row.cells.into_iter().map(area).fold(line, AlignLine::with))
+                });
+                if star {
+                    align = align.star();
+                }
+                Box::new(align)
+            }
+            Node::Label { key } => Box::new(Label::new(key)),
+            Node::Reference { kind, key } => {
+                let label = Label::new(key);
+                match kind {
+                    RefKind::Ref => Box::new(Ref::new(&label)),
+                    RefKind::PageRef => Box::new(PageRef::new(&label)),
+                    RefKind::EqRef => Box::new(EqRef::new(&label)),
+                    RefKind::Cref => Box::new(Cref::new(&label)),
+                }
+            }
+            Node::Cite {
+                command,
+                keys,
+                note,
+            } => {
+                let mut keys = keys.into_iter();
+                let mut cite = match keys.next() {
+                    Some(key) => keys.fold(Cite::new(command, key), Cite::key),
+                    // `\cite{}` is valid, it cites nothing
+                    None => Cite::new(command, ""),
+                };
+                if let Some(note) = note {
+                    cite = cite.note(note);
+                }
+                Box::new(cite)
+            }
+        }
+    }
+}
+
+impl Element for Node {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.clone().into_element().render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        self.clone().into_element().visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        self.clone()
+    }
+}
+
+/// The nodes of an element which may stand for several of them.
+pub(crate) fn flatten(node: Node) -> Vec<Node> {
+    match node {
+        Node::Sequence { body } => body,
+        node => vec![node],
+    }
+}
+
+fn area(nodes: Vec<Node>) -> Area<'static> {
+    with(Area::new(), nodes)
+}
+
+fn with<'a, C: Container<'a>>(container: C, nodes: Vec<Node>) -> C {
+    nodes.into_iter().fold(container, |container, node| {
+        container.with(node.into_element())
+    })
+}
+
+fn subfigure(item: SubFigureItem) -> SubFigure<'static> {
+    let mut subfigure = SubFigure::new();
+    if let Some(width) = item.width {
+        subfigure = subfigure.width(width);
+    }
+    if let Some(caption) = item.caption {
+        subfigure = subfigure.caption(caption);
+    }
+    if let Some(label) = item.label {
+        subfigure = subfigure.label(label.key()).0;
+    }
+
+    with(subfigure, item.body)
+}
+
+/// A document as data.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Outline {
+    #[cfg_attr(feature = "serde", serde(default))]
+    preambule: Preambule,
+    #[cfg_attr(feature = "serde", serde(default))]
+    body: Vec<Node>,
+}
+
+impl Document<'_> {
+    /// The content of the document as nodes.
+    pub fn to_nodes(&self) -> Vec<Node> {
+        self.body.nodes()
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_outline(&self) -> Outline {
+        let mut preambule = self.preambule.clone();
+        // elements kept as LaTeX don't tell which packages they need anymore
+        if let Ok(packages) = self.check() {
+            preambule.packages = packages;
+        }
+
+        Outline {
+            preambule,
+            body: self.to_nodes(),
+        }
+    }
+}
+
+impl From<Outline> for Document<'static> {
+    fn from(outline: Outline) -> Self {
+        let mut doc = Document::new();
+        doc.preambule = outline.preambule;
+        with(doc, outline.body)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Document<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_outline().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Document<'static> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Outline::deserialize(deserializer).map(Document::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cell, Expr};
+
+    fn document() -> Document<'static> {
+        let mut doc = Document::new();
+        doc.preambule().tittle("Report").author("Me");
+
+        let (intro, label) = Section::new("Intro").label("sec:intro");
+        doc.with(
+            intro.with(Text("See ")).with(Ref::new(&label)).with(
+                Enumerate::new()
+                    .start(3)
+                    .with(Text("one"))
+                    .item(Item::new().label("*").with(Raw("two"))),
+            ),
+        )
+        .with(
+            Table::new().caption("Numbers").with(
+                Tabular::new(vec![Column::Left, Column::Right])
+                    .toprule()
+                    .row(Row::new().cell(Cell::new(Text("a")).columns(2, vec![Column::Center])))
+                    .row(Row::new().with(Text("b")).with(Text("1")))
+                    .bottomrule(),
+            ),
+        )
+        .with(Figure::new().with(Graphics::new("plot.png").width("5cm").clip()))
+        .with(Equation::new(Expr::sym("x").pow(2)).label("eq:square").0)
+        .with(Environment::new("center").opt("t").with(Text("centered")))
+    }
+
+    #[test]
+    fn round_trip() {
+        let doc = document();
+        let nodes = doc.to_nodes();
+        assert!(matches!(
+            nodes[0],
+            Node::Heading {
+                level: Level::Section,
+                ..
+            }
+        ));
+        assert!(
+            matches!(nodes[3], Node::Equation { ref body, .. } if matches!(body[0], Node::Raw { .. }))
+        );
+
+        let rebuilt = Document::from(Outline {
+            preambule: doc.preambule.clone(),
+            body: nodes,
+        });
+        assert_eq!(doc.try_render().unwrap(), rebuilt.try_render().unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json() {
+        use crate::CommandDef;
+
+        let doc = document();
+
+        let json = serde_json::to_string(&doc).unwrap();
+        let rebuilt: Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(doc.try_render().unwrap(), rebuilt.try_render().unwrap());
+
+        let item: ListItem = serde_json::from_str(
+            r#"{"label": {"raw": "\\textbullet"}, "body": [{"type": "text", "text": "x"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(Some(Parameter::Raw("\\textbullet".to_owned())), item.label);
+
+        let title: Parameter = serde_json::from_str(r#""A & B""#).unwrap();
+        assert_eq!(Parameter::Literal("A & B".to_owned()), title);
+
+        // definitions are kept so duplicates are still found
+        let mut preambule = Preambule::new();
+        preambule.command(CommandDef::<0>::new("R", "")).unwrap();
+        let json = serde_json::to_string(&preambule).unwrap();
+        let mut rebuilt: Preambule = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            Err(Error::DuplicateDefinition("R".to_owned())),
+            rebuilt
+                .command(CommandDef::<0>::new("R", ""))
+                .map(|c| c.name().to_owned())
+        );
+    }
+}
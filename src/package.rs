@@ -0,0 +1,293 @@
+use crate::{Element, Error, Macros, Raw};
+use std::fmt;
+
+/// Options of a package which are mutually exclusive.
+const EXCLUSIVE: &[(&str, &[&str])] = &[
+    (
+        "inputenc",
+        &[
+            "ascii", "latin1", "latin2", "latin9", "utf8", "utf8x", "cp1250", "cp1252", "ansinew",
+            "applemac",
+        ],
+    ),
+    (
+        "geometry",
+        &[
+            "a4paper",
+            "a5paper",
+            "b5paper",
+            "letterpaper",
+            "legalpaper",
+            "executivepaper",
+        ],
+    ),
+    ("geometry", &["portrait", "landscape"]),
+];
+
+/// A `\usepackage` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Package {
+    name: String,
+    options: Vec<PackageOption>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum PackageOption {
+    /// `key`
+    Flag(String),
+    /// `key=value`
+    Value(String, String),
+}
+
+impl Package {
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            options: Vec::new(),
+        }
+    }
+
+    pub fn option<S: AsRef<str>>(mut self, key: S) -> Self {
+        self.options
+            .push(PackageOption::Flag(key.as_ref().to_owned()));
+        self
+    }
+
+    pub fn value<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.options.push(PackageOption::Value(
+            key.as_ref().to_owned(),
+            value.as_ref().to_owned(),
+        ));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn options(&self) -> &[PackageOption] {
+        &self.options
+    }
+
+    fn merge(&mut self, option: PackageOption) -> Result<(), Error> {
+        for present in &self.options {
+            if present == &option {
+                return Ok(());
+            }
+
+            if clash(&self.name, present, &option) {
+                return Err(Error::OptionClash {
+                    package: self.name.clone(),
+                    option: option.to_string(),
+                    other: present.to_string(),
+                });
+            }
+        }
+
+        self.options.push(option);
+        Ok(())
+    }
+}
+
+impl Element for Package {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut m = Macros::new("usepackage");
+        if !self.options.is_empty() {
+            let options = self
+                .options
+                .iter()
+                .map(|o| o.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            m = m.opt(Raw(options));
+        }
+
+        m.param(Raw(&self.name)).render_to(w)
+    }
+}
+
+impl PackageOption {
+    fn key(&self) -> &str {
+        match self {
+            Self::Flag(key) => key,
+            Self::Value(key, _) => key,
+        }
+    }
+}
+
+impl std::fmt::Display for PackageOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Flag(key) => f.write_str(key),
+            Self::Value(key, value) if value.contains(',') || value.contains('=') => {
+                write!(f, "{}={{{}}}", key, value)
+            }
+            Self::Value(key, value) => write!(f, "{}={}", key, value),
+        }
+    }
+}
+
+fn clash(package: &str, a: &PackageOption, b: &PackageOption) -> bool {
+    use PackageOption::*;
+
+    if a.key() == b.key() {
+        return match (a, b) {
+            (Value(_, x), Value(_, y)) => x != y,
+            (Flag(_), Value(_, v)) | (Value(_, v), Flag(_)) => v != "true",
+            (Flag(_), Flag(_)) => false,
+        };
+    }
+
+    let (a, b) = (a.to_string(), b.to_string());
+    EXCLUSIVE
+        .iter()
+        .filter(|(name, _)| *name == package)
+        .any(|(_, group)| group.contains(&a.as_str()) && group.contains(&b.as_str()))
+}
+
+/// A set of packages in the order they were first requested.
+///
+/// Requesting a package twice merges the options.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Packages {
+    list: Vec<Package>,
+}
+
+impl Packages {
+    pub fn new() -> Self {
+        Self { list: Vec::new() }
+    }
+
+    pub fn add(&mut self, package: Package) -> Result<(), Error> {
+        match self.list.iter_mut().find(|p| p.name == package.name) {
+            Some(present) => {
+                // check every option before touching the registry
+                let mut merged = present.clone();
+                for option in package.options {
+                    merged.merge(option)?;
+                }
+                *present = merged;
+            }
+            None => {
+                let mut fresh = Package::new(&package.name);
+                for option in package.options {
+                    fresh.merge(option)?;
+                }
+                self.list.push(fresh);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) {
+        self.list.retain(|p| p.name != name);
+    }
+
+    pub fn get<S: AsRef<str>>(&self, name: S) -> Option<&Package> {
+        self.list.iter().find(|p| p.name == name.as_ref())
+    }
+
+    pub fn contains<S: AsRef<str>>(&self, name: S) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Package> {
+        self.list.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
+
+impl Element for Packages {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        for (i, package) in self.list.iter().enumerate() {
+            if i > 0 {
+                w.write_char('\n')?;
+            }
+            package.render_to(w)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_duplicates_in_order() {
+        let mut packages = Packages::new();
+        packages.add(Package::new("amsmath")).unwrap();
+        packages
+            .add(Package::new("geometry").option("a4paper"))
+            .unwrap();
+        packages
+            .add(Package::new("amsmath").option("fleqn"))
+            .unwrap();
+        packages
+            .add(
+                Package::new("geometry")
+                    .value("margin", "2cm")
+                    .option("a4paper"),
+            )
+            .unwrap();
+
+        assert_eq!(
+            "\\usepackage[fleqn]{amsmath}\n\\usepackage[a4paper,margin=2cm]{geometry}",
+            packages.render()
+        );
+    }
+
+    #[test]
+    fn reports_clashes() {
+        let mut packages = Packages::new();
+        packages
+            .add(Package::new("inputenc").option("utf8"))
+            .unwrap();
+
+        let err = packages
+            .add(Package::new("inputenc").option("latin1"))
+            .unwrap_err();
+        assert_eq!(
+            Error::OptionClash {
+                package: "inputenc".to_owned(),
+                option: "latin1".to_owned(),
+                other: "utf8".to_owned(),
+            },
+            err
+        );
+
+        packages
+            .add(Package::new("hyperref").value("pdfborder", "{0 0 0}"))
+            .unwrap();
+        assert!(packages
+            .add(Package::new("hyperref").value("pdfborder", "{1 1 1}"))
+            .is_err());
+        assert_eq!(
+            &[PackageOption::Value(
+                "pdfborder".to_owned(),
+                "{0 0 0}".to_owned()
+            )],
+            packages.get("hyperref").unwrap().options()
+        );
+    }
+}
@@ -0,0 +1,931 @@
+//! Reading LaTeX source into elements.
+//!
+//! ```
+//! use trylatex::{parse, Element};
+//!
+//! let src = "\\documentclass[11pt]{article}
+//! \\usepackage{amsmath}
+//!
+//! \\begin{document}
+//! Hello % a comment
+//! \\textbf{world}
+//! \\end{document}
+//! ";
+//!
+//! let doc = parse::document(src).unwrap();
+//! assert_eq!(src, doc.into_document().render());
+//! ```
+
+use crate::{
+    node, Argument, ClassOption, Context, Document, DocumentType, Element, Error, FontSize, Label,
+    Macros, Package, PaperSize, Parameter, ParseError, Preambule, Raw,
+};
+use std::fmt;
+
+/// Environments which content isn't LaTeX or keeps its spacing.
+pub(crate) const VERBATIM: &[&str] = &[
+    "verbatim",
+    "verbatim*",
+    "Verbatim",
+    "Verbatim*",
+    "BVerbatim",
+    "BVerbatim*",
+    "LVerbatim",
+    "LVerbatim*",
+    "lstlisting",
+    "minted",
+    "comment",
+    "filecontents",
+    "filecontents*",
+    "alltt",
+];
+
+/// A piece of LaTeX source.
+///
+/// Nodes keep the source as it is, so rendering them gives back
+/// the text they were parsed from.
+#[derive(Clone)]
+pub enum Node {
+    /// Source text, rendered as is and not escaped.
+    Text(String),
+    /// A comment without the leading `%`.
+    Comment(String),
+    /// A macros with the groups and brackets right after it as arguments.
+    Macros(Macros),
+    Environment {
+        name: String,
+        args: Vec<Argument>,
+        body: Vec<Node>,
+    },
+    /// `{..}`
+    Group(Vec<Node>),
+}
+
+impl Element for Node {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        match self {
+            Node::Text(text) => w.write_str(text),
+            Node::Comment(comment) => write!(w, "%{}", comment),
+            Node::Macros(m) => m.render_to(w),
+            Node::Environment { name, args, body } => {
+                let mut begin = Macros::new("begin").param(Raw(name));
+                begin.args.extend(args.iter().cloned());
+
+                begin.render_to(w)?;
+                render(body, w)?;
+                Macros::new("end").param(Raw(name)).render_to(w)
+            }
+            Node::Group(nodes) => {
+                w.write_char('{')?;
+                render(nodes, w)?;
+                w.write_char('}')
+            }
+        }
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        match self {
+            // references aren't checked, they may point to labels
+            // in files which are `\input`
+            Node::Macros(m) if m.name() == "label" => {
+                if let Some(Argument::Mandatory(key)) = m.args().first() {
+                    ctx.define(&Label::new(key.render()));
+                }
+                Ok(())
+            }
+            Node::Environment { body, .. } | Node::Group(body) => {
+                body.iter().try_for_each(|node| node.visit(ctx))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn to_node(&self) -> node::Node {
+        match self {
+            Node::Text(text) => node::Node::Raw {
+                latex: text.clone(),
+            },
+            Node::Macros(m) => node::Node::Macros(m.clone()),
+            // `Environment` puts its content on lines of its own,
+            // the source has the line breaks already
+            Node::Environment { name, args, body } if !VERBATIM.contains(&name.as_str()) => {
+                match trim_lines(body) {
+                    Some(body) => node::Node::Environment {
+                        name: name.clone(),
+                        args: args.clone(),
+                        body: body.iter().map(Node::to_node).collect(),
+                    },
+                    None => node::Node::Raw {
+                        latex: self.render(),
+                    },
+                }
+            }
+            _ => node::Node::Raw {
+                latex: self.render(),
+            },
+        }
+    }
+}
+
+/// The nodes without the line breaks after `\begin` and before `\end`.
+fn trim_lines(body: &[Node]) -> Option<Vec<Node>> {
+    let mut body = body.to_vec();
+    match body.first_mut() {
+        Some(Node::Text(text)) if text.starts_with('\n') => text.remove(0),
+        _ => return None,
+    };
+    match body.last_mut() {
+        Some(Node::Text(text)) if text.ends_with('\n') => text.pop(),
+        _ => return None,
+    };
+    body.retain(|node| !matches!(node, Node::Text(text) if text.is_empty()));
+
+    Some(body)
+}
+
+fn render(nodes: &[Node], w: &mut dyn fmt::Write) -> fmt::Result {
+    nodes.iter().try_for_each(|node| node.render_to(w))
+}
+
+/// A parsed `.tex` file.
+pub struct ParsedDocument {
+    pub preambule: Preambule,
+    /// The content of the `document` environment.
+    pub body: Vec<Node>,
+}
+
+impl ParsedDocument {
+    pub fn into_document(self) -> Document<'static> {
+        let mut doc = Document::new();
+        *doc.preambule() = self.preambule;
+
+        self.body.into_iter().fold(doc, crate::Container::with)
+    }
+}
+
+/// Parses a piece of LaTeX source.
+pub fn nodes(src: &str) -> Result<Vec<Node>, ParseError> {
+    let mut parser = Parser::new(src);
+    parser.nodes(&Until::Eof)
+}
+
+/// Parses a whole `.tex` file.
+///
+/// The document class, packages, title and author go into the typed
+/// fields of [`Preambule`], the rest of the preambule is kept line by line
+/// in the source order around the packages. Anything after `\end{document}`
+/// is dropped as LaTeX ignores it.
+pub fn document(src: &str) -> Result<ParsedDocument, ParseError> {
+    let mut parser = Parser::new(src);
+    let preamble = parser.nodes(&Until::Document)?;
+    let mut body = parser.nodes(&Until::End("document".to_owned()))?;
+
+    // `Document` puts the content on its own lines
+    if let Some(Node::Text(text)) = body.first_mut() {
+        if text.starts_with('\n') {
+            text.remove(0);
+        }
+    }
+    if let Some(Node::Text(text)) = body.last_mut() {
+        if text.ends_with('\n') {
+            text.pop();
+        }
+    }
+    body.retain(|node| !matches!(node, Node::Text(text) if text.is_empty()));
+
+    Ok(ParsedDocument {
+        preambule: preambule_from(preamble, src)?,
+        body,
+    })
+}
+
+fn preambule_from(nodes: Vec<Node>, src: &str) -> Result<Preambule, ParseError> {
+    let mut preambule = Preambule::new();
+    let mut class = false;
+    let mut line = String::new();
+    // lines which go before the next package, if there's one
+    let mut pending = Vec::new();
+
+    fn flush(pending: &mut Vec<String>, line: &mut String) {
+        if !line.trim().is_empty() {
+            pending.push(line.trim().to_owned());
+        }
+        line.clear();
+    }
+
+    for node in nodes {
+        match node {
+            Node::Macros(m) if line.trim().is_empty() => match m.name() {
+                "documentclass" => {
+                    class_from(&mut preambule, &m);
+                    class = true;
+                }
+                "usepackage" => {
+                    for line in pending.drain(..) {
+                        preambule.line_before_packages(Raw(line));
+                    }
+                    packages_from(&mut preambule, &m, src)?
+                }
+                "title" | "author" if title_like(&m).is_some() => {
+                    let p = title_like(&m).cloned();
+                    match m.name() {
+                        "title" => preambule.tittle = p,
+                        _ => preambule.author = p,
+                    }
+                }
+                _ => line.push_str(&m.render()),
+            },
+            Node::Text(text) => {
+                for (i, part) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        flush(&mut pending, &mut line);
+                    }
+                    line.push_str(part);
+                }
+            }
+            node => line.push_str(&node.render()),
+        }
+    }
+    flush(&mut pending, &mut line);
+    for line in pending {
+        preambule.line(Raw(line));
+    }
+
+    if !class {
+        return Err(ParseError {
+            line: 1,
+            column: 1,
+            message: "expected \\documentclass".to_owned(),
+        });
+    }
+
+    Ok(preambule)
+}
+
+/// The argument of `\title{..}` or `\author{..}`, which have no other.
+fn title_like(m: &Macros) -> Option<&Parameter> {
+    match m.args() {
+        [Argument::Mandatory(p)] if !m.is_starred() => Some(p),
+        _ => None,
+    }
+}
+
+/// Optional and mandatory arguments rendered as they're in the source.
+fn arguments(m: &Macros) -> (Vec<String>, Vec<String>) {
+    let mut optional = Vec::new();
+    let mut mandatory = Vec::new();
+    for arg in m.args() {
+        match arg {
+            Argument::Optional(p) => optional.push(p.render()),
+            Argument::Mandatory(p) => mandatory.push(p.render()),
+        }
+    }
+
+    (optional, mandatory)
+}
+
+/// Splits a list of options at commas which aren't in braces.
+fn options(list: &str) -> Vec<String> {
+    let mut options = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in list.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                options.push(current.trim().to_owned());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    options.push(current.trim().to_owned());
+    options.retain(|o| !o.is_empty());
+
+    options
+}
+
+fn class_from(preambule: &mut Preambule, m: &Macros) {
+    let (optional, mandatory) = arguments(m);
+
+    let name = mandatory.first().map(|s| s.trim()).unwrap_or("article");
+    preambule.r#type(match name {
+        "article" => DocumentType::Article,
+        "report" => DocumentType::Report,
+        "book" => DocumentType::Book,
+        "letter" => DocumentType::Letter,
+        "beamer" => DocumentType::Beamer,
+        "memoir" => DocumentType::Memoir,
+        "scrartcl" => DocumentType::ScrArticle,
+        "scrreprt" => DocumentType::ScrReport,
+        "scrbook" => DocumentType::ScrBook,
+        "standalone" => DocumentType::Standalone,
+        other => DocumentType::custom(other),
+    });
+
+    for option in optional.iter().flat_map(|list| options(list)) {
+        preambule.class_option(match option.as_str() {
+            "a4paper" => ClassOption::Paper(PaperSize::A4),
+            "a5paper" => ClassOption::Paper(PaperSize::A5),
+            "b5paper" => ClassOption::Paper(PaperSize::B5),
+            "letterpaper" => ClassOption::Paper(PaperSize::Letter),
+            "legalpaper" => ClassOption::Paper(PaperSize::Legal),
+            "executivepaper" => ClassOption::Paper(PaperSize::Executive),
+            "10pt" => ClassOption::FontSize(FontSize::Pt10),
+            "11pt" => ClassOption::FontSize(FontSize::Pt11),
+            "12pt" => ClassOption::FontSize(FontSize::Pt12),
+            "onecolumn" => ClassOption::OneColumn,
+            "twocolumn" => ClassOption::TwoColumn,
+            "oneside" => ClassOption::OneSide,
+            "twoside" => ClassOption::TwoSide,
+            "draft" => ClassOption::Draft,
+            "final" => ClassOption::Final,
+            _ => ClassOption::Other(option),
+        });
+    }
+}
+
+fn packages_from(preambule: &mut Preambule, m: &Macros, src: &str) -> Result<(), ParseError> {
+    let (optional, mandatory) = arguments(m);
+
+    for name in mandatory.iter().flat_map(|list| options(list)) {
+        let mut package = Package::new(name);
+        for option in optional.iter().flat_map(|list| options(list)) {
+            package = match option.split_once('=') {
+                Some((key, value)) => {
+                    let value = value.trim();
+                    let value = value
+                        .strip_prefix('{')
+                        .and_then(|v| v.strip_suffix('}'))
+                        .unwrap_or(value);
+                    package.value(key.trim(), value)
+                }
+                None => package.option(option),
+            };
+        }
+
+        preambule.package(package).map_err(|err| {
+            // a clash can only be found knowing what was before, so point at the macros
+            let at = src.find(&m.render()).unwrap_or(0);
+            let (line, column) = position(src, at);
+            ParseError {
+                line,
+                column,
+                message: err.to_string(),
+            }
+        })?;
+    }
+
+    Ok(())
+}
+
+fn position(src: &str, at: usize) -> (usize, usize) {
+    let before = &src[..at];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+
+    (line, column)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    /// `\name`
+    Word(String),
+    /// `\` followed by a single character which isn't a letter
+    Symbol(char),
+    Open,
+    Close,
+    LBracket,
+    RBracket,
+    Star,
+    Comment(String),
+    Text(String),
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+    line: usize,
+    column: usize,
+}
+
+struct Lexer<'s> {
+    src: &'s str,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'s> Lexer<'s> {
+    fn new(src: &'s str) -> Self {
+        Self {
+            src,
+            pos: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn next(&mut self) -> Token {
+        let (start, line, column) = (self.pos, self.line, self.column);
+
+        let kind = match self.bump() {
+            None => TokenKind::Eof,
+            Some('\\') => match self.peek_char() {
+                Some(c) if c.is_ascii_alphabetic() => {
+                    let mut name = String::new();
+                    while let Some(c) = self.peek_char().filter(|c| c.is_ascii_alphabetic()) {
+                        name.push(c);
+                        self.bump();
+                    }
+                    TokenKind::Word(name)
+                }
+                Some(c) => {
+                    self.bump();
+                    TokenKind::Symbol(c)
+                }
+                None => TokenKind::Text("\\".to_owned()),
+            },
+            Some('{') => TokenKind::Open,
+            Some('}') => TokenKind::Close,
+            Some('[') => TokenKind::LBracket,
+            Some(']') => TokenKind::RBracket,
+            Some('*') => TokenKind::Star,
+            Some('%') => {
+                let mut comment = String::new();
+                while let Some(c) = self.peek_char().filter(|&c| c != '\n') {
+                    comment.push(c);
+                    self.bump();
+                }
+                TokenKind::Comment(comment)
+            }
+            Some(c) => {
+                let mut text = c.to_string();
+                while let Some(c) = self
+                    .peek_char()
+                    .filter(|c| !matches!(c, '\\' | '{' | '}' | '[' | ']' | '*' | '%'))
+                {
+                    text.push(c);
+                    self.bump();
+                }
+                TokenKind::Text(text)
+            }
+        };
+
+        Token {
+            kind,
+            start,
+            end: self.pos,
+            line,
+            column,
+        }
+    }
+
+    /// Reads the source as is up to the terminator, which is consumed.
+    fn raw_until(&mut self, terminator: &str) -> Option<String> {
+        let len = self.src[self.pos..].find(terminator)?;
+        let raw = self.src[self.pos..self.pos + len].to_owned();
+        for _ in raw.chars().chain(terminator.chars()) {
+            self.bump();
+        }
+
+        Some(raw)
+    }
+
+    fn rewind(&mut self, token: &Token) {
+        self.pos = token.start;
+        self.line = token.line;
+        self.column = token.column;
+    }
+}
+
+enum Until {
+    Eof,
+    Close,
+    /// `\begin{document}`
+    Document,
+    /// `\end{name}`
+    End(String),
+}
+
+struct Parser<'s> {
+    lexer: Lexer<'s>,
+    peeked: Option<Token>,
+}
+
+impl<'s> Parser<'s> {
+    fn new(src: &'s str) -> Self {
+        Self {
+            lexer: Lexer::new(src),
+            peeked: None,
+        }
+    }
+
+    fn next(&mut self) -> Token {
+        match self.peeked.take() {
+            Some(token) => token,
+            None => self.lexer.next(),
+        }
+    }
+
+    fn peek(&mut self) -> &TokenKind {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.lexer.next());
+        }
+
+        &self.peeked.as_ref().expect("just peeked").kind
+    }
+
+    fn nodes(&mut self, until: &Until) -> Result<Vec<Node>, ParseError> {
+        let mut nodes = Vec::new();
+        loop {
+            let token = self.next();
+            match token.kind {
+                TokenKind::Eof => {
+                    return match until {
+                        Until::Eof => Ok(nodes),
+                        Until::Close => Err(error(&token, "missing '}'")),
+                        Until::Document => Err(error(&token, "missing \\begin{document}")),
+                        Until::End(name) => {
+                            Err(error(&token, format!("missing \\end{{{}}}", name)))
+                        }
+                    }
+                }
+                TokenKind::Close => {
+                    return match until {
+                        Until::Close => Ok(nodes),
+                        _ => Err(error(&token, "unbalanced '}'")),
+                    }
+                }
+                TokenKind::Text(text) => push_text(&mut nodes, &text),
+                TokenKind::LBracket => push_text(&mut nodes, "["),
+                TokenKind::RBracket => push_text(&mut nodes, "]"),
+                TokenKind::Star => push_text(&mut nodes, "*"),
+                TokenKind::Comment(comment) => nodes.push(Node::Comment(comment)),
+                TokenKind::Open => nodes.push(Node::Group(self.nodes(&Until::Close)?)),
+                TokenKind::Word(ref word) if word == "begin" => {
+                    let name = self.name(&token)?;
+                    if let Until::Document = until {
+                        if name == "document" {
+                            return Ok(nodes);
+                        }
+                    }
+
+                    nodes.push(self.environment(name, &token)?);
+                }
+                TokenKind::Word(ref word) if word == "end" => {
+                    let name = self.name(&token)?;
+                    return match until {
+                        Until::End(expected) if *expected == name => Ok(nodes),
+                        Until::End(expected) => Err(error(
+                            &token,
+                            format!("expected \\end{{{}}}, found \\end{{{}}}", expected, name),
+                        )),
+                        _ => Err(error(&token, format!("unexpected \\end{{{}}}", name))),
+                    };
+                }
+                TokenKind::Word(ref word) if word == "verb" => {
+                    let verb = self.verb(&token)?;
+                    push_text(&mut nodes, &verb);
+                }
+                TokenKind::Word(word) => {
+                    let m = self.arguments(Macros::new(word), true)?;
+                    nodes.push(Node::Macros(m));
+                }
+                TokenKind::Symbol('\\') => {
+                    // a line break takes an optional space, `\\[2pt]`
+                    let m = self.arguments(Macros::new("\\"), false)?;
+                    nodes.push(Node::Macros(m));
+                }
+                TokenKind::Symbol(c) => nodes.push(Node::Macros(Macros::new(c.to_string()))),
+            }
+        }
+    }
+
+    fn environment(&mut self, name: String, begin: &Token) -> Result<Node, ParseError> {
+        let m = self.arguments(Macros::new("begin"), false)?;
+        let args = m.args;
+
+        let body = if VERBATIM.contains(&name.as_str()) {
+            if let Some(token) = self.peeked.take() {
+                self.lexer.rewind(&token);
+            }
+
+            let end = format!("\\end{{{}}}", name);
+            match self.lexer.raw_until(&end) {
+                Some(raw) => vec![Node::Text(raw)],
+                None => return Err(error(begin, format!("missing {}", end))),
+            }
+        } else {
+            self.nodes(&Until::End(name.clone()))?
+        };
+
+        Ok(Node::Environment { name, args, body })
+    }
+
+    /// Reads the name of an environment after `\begin` or `\end`.
+    fn name(&mut self, token: &Token) -> Result<String, ParseError> {
+        if self.peek() != &TokenKind::Open {
+            return Err(error(token, "expected a name of an environment"));
+        }
+        let open = self.next();
+
+        let start = open.end;
+        let end = self.skip_group(&open)?;
+        Ok(self.lexer.src[start..end].trim().to_owned())
+    }
+
+    /// Reads `\verb|..|` as is.
+    fn verb(&mut self, token: &Token) -> Result<String, ParseError> {
+        if let Some(token) = self.peeked.take() {
+            self.lexer.rewind(&token);
+        }
+
+        let mut verb = String::from("\\verb");
+        if self.lexer.peek_char() == Some('*') {
+            verb.push('*');
+            self.lexer.bump();
+        }
+        let delimiter = match self.lexer.bump() {
+            Some(c) if !c.is_whitespace() => c,
+            _ => return Err(error(token, "expected a delimiter after \\verb")),
+        };
+        let raw = self
+            .lexer
+            .raw_until(&delimiter.to_string())
+            .filter(|raw| !raw.contains('\n'))
+            .ok_or_else(|| error(token, "unterminated \\verb"))?;
+
+        verb.push(delimiter);
+        verb.push_str(&raw);
+        verb.push(delimiter);
+
+        Ok(verb)
+    }
+
+    /// Takes the star, brackets and groups right after a macros as its arguments.
+    fn arguments(&mut self, mut m: Macros, star: bool) -> Result<Macros, ParseError> {
+        if star && self.peek() == &TokenKind::Star {
+            self.next();
+            m = m.star();
+        }
+
+        loop {
+            match self.peek() {
+                TokenKind::Open => {
+                    let open = self.next();
+                    let end = self.skip_group(&open)?;
+                    m = m.param(Raw(&self.lexer.src[open.end..end]));
+                }
+                TokenKind::LBracket => {
+                    let open = self.next();
+                    let end = self.skip_optional(&open)?;
+                    m = m.opt(Raw(&self.lexer.src[open.end..end]));
+                }
+                _ => return Ok(m),
+            }
+        }
+    }
+
+    /// Skips to the matching `}` and returns where it starts.
+    fn skip_group(&mut self, open: &Token) -> Result<usize, ParseError> {
+        let mut depth = 0;
+        loop {
+            let token = self.next();
+            match token.kind {
+                TokenKind::Open => depth += 1,
+                TokenKind::Close if depth == 0 => return Ok(token.start),
+                TokenKind::Close => depth -= 1,
+                TokenKind::Eof => return Err(error(open, "missing '}'")),
+                _ => {}
+            }
+        }
+    }
+
+    /// Skips to the `]` which isn't in a group and returns where it starts.
+    fn skip_optional(&mut self, open: &Token) -> Result<usize, ParseError> {
+        let mut depth = 0;
+        loop {
+            let token = self.next();
+            match token.kind {
+                TokenKind::Open => depth += 1,
+                TokenKind::Close if depth == 0 => return Err(error(&token, "unbalanced '}'")),
+                TokenKind::Close => depth -= 1,
+                TokenKind::RBracket if depth == 0 => return Ok(token.start),
+                TokenKind::Eof => return Err(error(open, "missing ']'")),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn push_text(nodes: &mut Vec<Node>, text: &str) {
+    match nodes.last_mut() {
+        Some(Node::Text(last)) => last.push_str(text),
+        _ => nodes.push(Node::Text(text.to_owned())),
+    }
+}
+
+fn error<S: Into<String>>(token: &Token, message: S) -> ParseError {
+    ParseError {
+        line: token.line,
+        column: token.column,
+        message: message.into(),
+    }
+}
+
+impl From<Node> for Parameter {
+    fn from(node: Node) -> Parameter {
+        match node {
+            Node::Macros(m) => Parameter::Macros(m),
+            node => Parameter::Raw(node.render()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Container, Document, Ref, Text};
+
+    const SRC: &str = r"\documentclass[a4paper,11pt]{report}
+% packages
+\usepackage[utf8]{inputenc}
+\usepackage{amsmath, graphicx}
+\usepackage[margin=2cm,paper={a4paper}]{geometry}
+\newcommand{\R}{\mathbb{R}} % reals
+\def\x#1{#1}
+\title{On \LaTeX}
+\author{Me}
+
+\begin{document}
+\chapter*{Intro}\label{ch:intro}
+Some text with \emph{emphasis}, a \verb|\verb{| and math $x^2$.
+\\[2pt]
+\begin{itemize}[noitemsep]
+  \item[--] one % comment
+  \item two
+\end{itemize}
+\begin{verbatim}
+\end{itemize} { unbalanced
+\end{verbatim}
+\end{document}
+ignored
+";
+
+    #[test]
+    fn round_trip() {
+        let parsed = document(SRC).unwrap();
+
+        assert_eq!(&DocumentType::Report, &parsed.preambule.r#type);
+        assert_eq!(
+            vec!["inputenc", "amsmath", "graphicx", "geometry"],
+            parsed
+                .preambule
+                .packages()
+                .iter()
+                .map(|p| p.name())
+                .collect::<Vec<_>>()
+        );
+
+        let expected = r"\documentclass[a4paper,11pt]{report}
+% packages
+\usepackage[utf8]{inputenc}
+\usepackage{amsmath}
+\usepackage{graphicx}
+\usepackage[margin=2cm,paper=a4paper]{geometry}
+\newcommand{\R}{\mathbb{R}} % reals
+\def\x#1{#1}
+\title{On \LaTeX}
+\author{Me}
+
+\begin{document}
+\chapter*{Intro}\label{ch:intro}
+Some text with \emph{emphasis}, a \verb|\verb{| and math $x^2$.
+\\[2pt]
+\begin{itemize}[noitemsep]
+  \item[--] one % comment
+  \item two
+\end{itemize}
+\begin{verbatim}
+\end{itemize} { unbalanced
+\end{verbatim}
+\end{document}
+";
+        let rendered = parsed.into_document().render();
+        assert_eq!(expected, rendered);
+
+        // rendering is stable
+        assert_eq!(
+            rendered,
+            document(&rendered).unwrap().into_document().render()
+        );
+    }
+
+    #[test]
+    fn preambule_order() {
+        let src = r"\documentclass{article}
+\PassOptionsToPackage{hyphens}{url}
+\usepackage{url}
+\def\x{1}
+\usepackage{amsmath}
+\usepackage{url}
+\newcommand{\y}{2}
+
+\begin{document}
+x
+\end{document}
+";
+        let rendered = document(src).unwrap().into_document().render();
+        assert_eq!(
+            src.replace("\\usepackage{url}\n\\newcommand", "\\newcommand"),
+            rendered
+        );
+
+        let mut doc = document(src).unwrap().into_document();
+        doc.preambule().use_package("graphicx");
+        assert!(doc
+            .render()
+            .contains("\\def\\x{1}\n\\usepackage{amsmath}\n\\usepackage{graphicx}\n\\newcommand"));
+    }
+
+    #[test]
+    fn nodes_structure() {
+        let nodes = nodes(r"\section*[Short]{Title} {\bf x}").unwrap();
+        match &nodes[0] {
+            Node::Macros(m) => {
+                assert_eq!("section", m.name());
+                assert!(m.is_starred());
+                assert_eq!(2, m.args().len());
+            }
+            _ => panic!("expected a macros"),
+        }
+        assert!(matches!(&nodes[1], Node::Text(t) if t == " "));
+        assert!(matches!(&nodes[2], Node::Group(g) if g.len() == 2));
+    }
+
+    #[test]
+    fn labels_are_known() {
+        let parsed = document("\\documentclass{article}\n\\begin{document}\n\\section{A}\\label{sec:a}\n\\end{document}").unwrap();
+        let doc = parsed
+            .into_document()
+            .with(Text("see "))
+            .with(Ref::new(&Label::new("sec:a")));
+        assert!(doc.try_render().is_ok());
+
+        let doc: Document = Document::new().with(Ref::new(&Label::new("sec:a")));
+        assert!(doc.try_render().is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Err(ParseError {
+                line: 2,
+                column: 3,
+                message: "expected \\end{itemize}, found \\end{enumerate}".to_owned()
+            }),
+            nodes("\\begin{itemize}\n  \\end{enumerate}").map(|_| ())
+        );
+        assert_eq!(
+            Err(ParseError {
+                line: 1,
+                column: 3,
+                message: "unbalanced '}'".to_owned()
+            }),
+            nodes("a }").map(|_| ())
+        );
+        assert_eq!(
+            Err(ParseError {
+                line: 1,
+                column: 6,
+                message: "missing '}'".to_owned()
+            }),
+            nodes("\\emph{x").map(|_| ())
+        );
+        assert!(document("\\begin{document}\\end{document}").is_err());
+    }
+}
@@ -0,0 +1,537 @@
+use std::fmt;
+
+use crate::parse::VERBATIM;
+use crate::Element;
+
+/// Macros which argument is taken as is, a `%` in it would be a part of it.
+const LITERAL_ARGUMENTS: &[&str] = &["\\url{", "\\href{"];
+
+/// Headings which get blank lines before them.
+const HEADINGS: &[&str] = &[
+    "part",
+    "chapter",
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraph",
+    "subparagraph",
+];
+
+/// How rendered LaTeX is laid out.
+///
+/// Only whitespace which TeX ignores is changed, so the typeset
+/// document stays the same. Environments which keep their spacing,
+/// like `verbatim`, `Verbatim`, `filecontents` and `alltt`,
+/// and the addresses of `\url` and `\href` are left as they are.
+///
+/// ```
+/// use trylatex::{Container, Element, Itemize, RenderConfig, Text};
+///
+/// let list = Itemize::new().with(Text("one")).with(Text("two"));
+/// assert_eq!(
+///     "\\begin{itemize}\n  \\item one\n  \\item two\n\\end{itemize}",
+///     RenderConfig::new().render(&list)
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderConfig {
+    indent: usize,
+    indent_environments: bool,
+    wrap: Option<usize>,
+    blank_lines: usize,
+    percent: bool,
+}
+
+impl RenderConfig {
+    pub fn new() -> Self {
+        Self {
+            indent: 2,
+            indent_environments: true,
+            wrap: None,
+            blank_lines: 1,
+            percent: true,
+        }
+    }
+
+    /// Sets how many spaces a level of indentation takes, 2 by default.
+    pub fn indent(mut self, width: usize) -> Self {
+        self.indent = width;
+        self
+    }
+
+    /// Indents the bodies of environments, except `document`.
+    pub fn indent_environments(mut self, indent: bool) -> Self {
+        self.indent_environments = indent;
+        self
+    }
+
+    /// Wraps lines longer than the column at spaces.
+    ///
+    /// Lines with comments or `\verb` aren't wrapped.
+    pub fn wrap(mut self, column: usize) -> Self {
+        self.wrap = Some(column);
+        self
+    }
+
+    /// Sets how many blank lines separate paragraphs and go before headings.
+    ///
+    /// There's always one between paragraphs, as without it they'd be joined.
+    pub fn blank_lines(mut self, n: usize) -> Self {
+        self.blank_lines = n;
+        self
+    }
+
+    /// Lets a line without spaces be wrapped after a `}`,
+    /// the line ends with `%` so no space appears there.
+    pub fn percent(mut self, percent: bool) -> Self {
+        self.percent = percent;
+        self
+    }
+
+    pub fn render(&self, e: &dyn Element) -> String {
+        let mut buf = String::new();
+        self.render_to(e, &mut buf)
+            .expect("writing into a String doesn't fail");
+        buf
+    }
+
+    pub fn render_to(&self, e: &dyn Element, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut w = PrettyWriter::new(self, w);
+        e.render_to(&mut w)?;
+        w.finish()
+    }
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lays out what's written to it line by line.
+///
+/// [`PrettyWriter::finish`] must be called to write the last line.
+pub struct PrettyWriter<'c, W: fmt::Write> {
+    config: &'c RenderConfig,
+    inner: W,
+    line: String,
+    depth: usize,
+    /// The name of a verbatim environment which content is kept as is.
+    verbatim: Option<String>,
+    blank: usize,
+    started: bool,
+    newline: bool,
+}
+
+impl<'c, W: fmt::Write> PrettyWriter<'c, W> {
+    pub fn new(config: &'c RenderConfig, inner: W) -> Self {
+        Self {
+            config,
+            inner,
+            line: String::new(),
+            depth: 0,
+            verbatim: None,
+            blank: 0,
+            started: false,
+            newline: false,
+        }
+    }
+
+    pub fn finish(mut self) -> fmt::Result {
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            self.newline = false;
+            self.line(&line)?;
+        }
+        if self.newline {
+            self.inner.write_char('\n')?;
+        }
+
+        Ok(())
+    }
+
+    fn line(&mut self, line: &str) -> fmt::Result {
+        if self.verbatim.is_some() {
+            return self.process(line);
+        }
+
+        split_headings(line)
+            .into_iter()
+            .try_for_each(|line| self.process(line))
+    }
+
+    fn process(&mut self, line: &str) -> fmt::Result {
+        if let Some(name) = &self.verbatim {
+            let end = format!("\\end{{{}}}", name);
+            if !line.trim_start().starts_with(&end) {
+                return self.start_line().and_then(|_| self.inner.write_str(line));
+            }
+            // the rest of the line is LaTeX again
+            self.verbatim = None;
+        }
+
+        let line = line.trim();
+        if line.is_empty() {
+            self.blank += 1;
+            return Ok(());
+        }
+
+        let code = code(line);
+        let ends_first = line.starts_with("\\end{");
+        if ends_first && !line.starts_with("\\end{document}") {
+            self.depth = self.depth.saturating_sub(1);
+        }
+
+        let heading = line.strip_prefix('\\').is_some_and(is_heading);
+        self.emit(line, heading)?;
+
+        let mut opened = names(code, "\\begin{");
+        let closed = names(code, "\\end{");
+        for (i, name) in closed.iter().enumerate() {
+            if i == 0 && ends_first {
+                continue;
+            }
+            match opened.iter().rposition(|n| n == name) {
+                Some(i) => {
+                    opened.remove(i);
+                }
+                None if name != "document" => self.depth = self.depth.saturating_sub(1),
+                None => {}
+            }
+        }
+        for name in opened {
+            if name != "document" {
+                self.depth += 1;
+            }
+            if VERBATIM.contains(&name.as_str()) {
+                self.verbatim = Some(name);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn start_line(&mut self) -> fmt::Result {
+        if self.started {
+            self.inner.write_char('\n')?;
+        }
+        self.started = true;
+        Ok(())
+    }
+
+    fn emit(&mut self, line: &str, heading: bool) -> fmt::Result {
+        let blank = if !self.started {
+            0
+        } else if self.blank > 0 {
+            self.config.blank_lines.max(1)
+        } else if heading {
+            self.config.blank_lines
+        } else {
+            0
+        };
+        self.blank = 0;
+
+        self.start_line()?;
+        for _ in 0..blank {
+            self.inner.write_char('\n')?;
+        }
+
+        let indent = if self.config.indent_environments {
+            " ".repeat(self.depth * self.config.indent)
+        } else {
+            String::new()
+        };
+
+        // wrapping a comment would move a part of it out
+        let lines = match self.config.wrap {
+            Some(column) if code(line) == line && !line.contains("\\verb") => wrap(
+                line,
+                column.saturating_sub(indent.len()),
+                self.config.percent,
+            ),
+            _ => vec![line.to_owned()],
+        };
+
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                self.inner.write_char('\n')?;
+            }
+            self.inner.write_str(&indent)?;
+            self.inner.write_str(line)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for PrettyWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while let Some(i) = rest.find('\n') {
+            self.line.push_str(&rest[..i]);
+            let line = std::mem::take(&mut self.line);
+            self.line(&line)?;
+            rest = &rest[i + 1..];
+            self.newline = true;
+        }
+        if !rest.is_empty() {
+            self.line.push_str(rest);
+            self.newline = false;
+        }
+
+        Ok(())
+    }
+}
+
+fn is_heading(command: &str) -> bool {
+    let name = command
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default();
+
+    HEADINGS.contains(&name)
+}
+
+/// Puts headings in the middle of a line on their own lines,
+/// which is safe as a heading starts a new paragraph anyway.
+fn split_headings(line: &str) -> Vec<&str> {
+    let code = code(line);
+    if code.contains("\\verb") || VERBATIM.iter().any(|v| code.contains(v)) {
+        return vec![line];
+    }
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+    let mut chars = code.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '\\' => {
+                if depth == 0 && i > start && is_heading(&code[i + 1..]) {
+                    lines.push(&line[start..i]);
+                    start = i;
+                }
+                // skips an escaped character, e.g. `\\` or `\{`
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    lines.push(&line[start..]);
+
+    lines
+}
+
+/// The line without a comment.
+fn code(line: &str) -> &str {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '%' if !escaped => return &line[..i],
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+
+    line
+}
+
+fn names(code: &str, prefix: &str) -> Vec<String> {
+    code.match_indices(prefix)
+        .filter_map(|(i, _)| {
+            let rest = &code[i + prefix.len()..];
+            rest.find('}').map(|end| rest[..end].to_owned())
+        })
+        .collect()
+}
+
+/// Splits a line at spaces so the parts fit in the width.
+fn wrap(line: &str, width: usize, percent: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut rest = line;
+    while rest.chars().count() > width {
+        // the byte offset of the character at the width
+        let limit = rest
+            .char_indices()
+            .nth(width)
+            .map_or(rest.len(), |(i, _)| i);
+
+        let literal = literal_arguments(rest);
+        // a break before the byte doesn't split an address
+        let breakable = |i: usize| !literal.iter().any(|&(open, close)| open < i && i <= close);
+
+        let at_space = if rest[limit..].starts_with(' ') && breakable(limit) {
+            Some(limit)
+        } else {
+            rest[..limit]
+                .match_indices(' ')
+                .map(|(i, _)| i)
+                .rev()
+                .find(|&i| i > 0 && breakable(i))
+        };
+        let at_brace = || {
+            rest[..limit]
+                .match_indices('}')
+                .map(|(i, _)| i + 1)
+                .rev()
+                .find(|&i| breakable(i))
+                .filter(|&i| i < rest.len() && !rest[i..].starts_with(' '))
+        };
+
+        match at_space {
+            Some(i) => {
+                lines.push(rest[..i].trim_end().to_owned());
+                rest = rest[i..].trim_start();
+            }
+            None => match at_brace().filter(|_| percent) {
+                Some(i) => {
+                    lines.push(format!("{}%", &rest[..i]));
+                    rest = &rest[i..];
+                }
+                // a long word is left on its own line
+                None => match rest[limit..]
+                    .match_indices(' ')
+                    .map(|(i, _)| limit + i)
+                    .find(|&i| breakable(i))
+                {
+                    Some(i) => {
+                        lines.push(rest[..i].to_owned());
+                        rest = rest[i..].trim_start();
+                    }
+                    None => break,
+                },
+            },
+        }
+    }
+    if !rest.is_empty() {
+        lines.push(rest.to_owned());
+    }
+
+    lines
+}
+
+/// Byte offsets of the braces around the addresses of `\url` and `\href`.
+fn literal_arguments(line: &str) -> Vec<(usize, usize)> {
+    LITERAL_ARGUMENTS
+        .iter()
+        .flat_map(|prefix| line.match_indices(prefix))
+        .map(|(i, prefix)| {
+            let open = i + prefix.len() - 1;
+            let mut depth = 0;
+            let close = line[open..]
+                .char_indices()
+                .find(|&(_, c)| {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })
+                .map_or(line.len(), |(j, _)| open + j);
+            (open, close)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Container, Document, Environment, Raw, Section, Subsection, Text};
+
+    #[test]
+    fn indentation_and_headings() {
+        let doc = Document::new()
+            .with(
+                Section::new("One")
+                    .with(Text("Intro."))
+                    .with(Subsection::new("Two").with(Raw("a\n\n\n\nb"))),
+            )
+            .with(
+                Environment::new("center")
+                    .with(Environment::new("verbatim").with(Raw("keep\n    as is")))
+                    .with(Environment::new("Verbatim").with(Raw("keep"))),
+            );
+
+        let expected = r"\documentclass{article}
+
+\begin{document}
+
+\section{One}
+Intro.
+
+\subsection{Two}
+a
+
+b\begin{center}
+  \begin{verbatim}
+keep
+    as is
+  \end{verbatim}\begin{Verbatim}
+keep
+  \end{Verbatim}
+\end{center}
+\end{document}
+";
+        assert_eq!(expected, RenderConfig::new().render(&doc));
+
+        let expected = r"\begin{center}
+\begin{verbatim}
+keep
+    as is
+\end{verbatim}
+\end{center}";
+        let center = Environment::new("center")
+            .with(Environment::new("verbatim").with(Raw("keep\n    as is")));
+        assert_eq!(
+            expected,
+            RenderConfig::new()
+                .indent_environments(false)
+                .blank_lines(0)
+                .render(&center)
+        );
+    }
+
+    #[test]
+    fn wrapping() {
+        let center = Environment::new("center").with(Text(
+            "a few words which don't fit into a line of twenty characters",
+        ));
+        assert_eq!(
+            "\\begin{center}\n  a few words which\n  don't fit into a\n  line of twenty\n  characters\n\\end{center}",
+            RenderConfig::new().wrap(20).render(&center)
+        );
+
+        let macros = Raw("\\textbf{bold}\\emph{emphasis}\\textit{italic}");
+        assert_eq!(
+            "\\textbf{bold}\\emph{emphasis}%\n\\textit{italic}",
+            RenderConfig::new().wrap(30).render(&macros)
+        );
+        assert_eq!(
+            "\\textbf{bold}\\emph{emphasis}\\textit{italic}",
+            RenderConfig::new().wrap(30).percent(false).render(&macros)
+        );
+
+        // a `%` or a line break would be a part of the address
+        let link =
+            Raw("see \\url{https://example.com/{b}/c}\\href{https://example.com/a b}{the docs}");
+        assert_eq!(
+            "see\n\\url{https://example.com/{b}/c}\\href{https://example.com/a b}{the\ndocs}",
+            RenderConfig::new().wrap(12).render(&link)
+        );
+        let link = Raw("\\url{a{b}cccccccc}");
+        assert_eq!(
+            "\\url{a{b}cccccccc}",
+            RenderConfig::new().wrap(10).render(&link)
+        );
+
+        // comments aren't wrapped as the rest would be uncommented
+        let comment = Raw("text % a comment which is rather long");
+        assert_eq!(
+            "text % a comment which is rather long",
+            RenderConfig::new().wrap(10).render(&comment)
+        );
+    }
+}
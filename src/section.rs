@@ -0,0 +1,234 @@
+use crate::node::{Level, Node};
+use crate::{Area, Container, Context, Element, Error, Label, Macros, Parameter};
+use std::fmt;
+
+struct Heading<'a> {
+    command: &'static str,
+    title: Parameter,
+    short: Option<Parameter>,
+    starred: bool,
+    label: Option<Label>,
+    body: Area<'a>,
+}
+
+impl Heading<'_> {
+    fn new(command: &'static str, title: Parameter) -> Self {
+        Self {
+            command,
+            title,
+            short: None,
+            starred: false,
+            label: None,
+            body: Area::new(),
+        }
+    }
+}
+
+impl Element for Heading<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut m = Macros::new(self.command);
+        if self.starred {
+            // starred headings don't go to the table of contents
+            m = m.star();
+        } else if let Some(short) = &self.short {
+            m = m.opt(short.clone());
+        }
+
+        m.param(self.title.clone()).render_to(w)?;
+        if let Some(label) = &self.label {
+            label.render_to(w)?;
+        }
+
+        if !self.body.is_empty() {
+            w.write_char('\n')?;
+            self.body.render_to(w)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        if self.starred && self.short.is_some() {
+            return Err(Error::StarredShortTitle(format!("\\{}*", self.command)));
+        }
+        if let Some(label) = &self.label {
+            label.visit(ctx)?;
+        }
+
+        self.body.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        Node::Heading {
+            level: Level::from_command(self.command).expect("headings have a level"),
+            title: self.title.clone(),
+            short: self.short.clone(),
+            star: self.starred,
+            label: self.label.clone(),
+            body: self.body.to_nodes(),
+        }
+    }
+}
+
+macro_rules! heading {
+    ($(#[$doc:meta])* $name:ident, $command:literal) => {
+        $(#[$doc])*
+        pub struct $name<'a>(Heading<'a>);
+
+        impl $name<'_> {
+            pub fn new<P: Into<Parameter>>(title: P) -> Self {
+                Self(Heading::new($command, title.into()))
+            }
+
+            /// Makes the heading unnumbered.
+            pub fn star(mut self) -> Self {
+                self.0.starred = true;
+                self
+            }
+
+            /// Sets a title used in the table of contents and running heads,
+            /// a starred heading has none of them so it's an error there.
+            pub fn short<P: Into<Parameter>>(mut self, title: P) -> Self {
+                self.0.short = Some(title.into());
+                self
+            }
+
+            /// Labels the heading, references are made with the returned label.
+            pub fn label<S: AsRef<str>>(mut self, key: S) -> (Self, Label) {
+                let label = Label::new(key);
+                self.0.label = Some(label.clone());
+                (self, label)
+            }
+        }
+
+        impl<'a> Container<'a> for $name<'a> {
+            fn with<E: Element + 'a>(mut self, e: E) -> Self {
+                self.0.body = self.0.body.with(e);
+                self
+            }
+        }
+    };
+}
+
+heading!(Part, "part");
+heading!(
+    /// Available only in classes which have chapters, e.g. `report` or `book`.
+    Chapter,
+    "chapter"
+);
+heading!(Section, "section");
+heading!(Subsection, "subsection");
+heading!(Subsubsection, "subsubsection");
+heading!(Paragraph, "paragraph");
+heading!(Subparagraph, "subparagraph");
+
+macro_rules! element {
+    ($($name:ident),*) => {
+        $(
+            impl Element for $name<'_> {
+                fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+                    self.0.render_to(w)
+                }
+
+                fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+                    self.0.visit(ctx)
+                }
+
+                fn to_node(&self) -> Node {
+                    self.0.to_node()
+                }
+            }
+        )*
+    };
+}
+
+element!(
+    Part,
+    Section,
+    Subsection,
+    Subsubsection,
+    Paragraph,
+    Subparagraph
+);
+
+impl Element for Chapter<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.0.render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        if !ctx.class().has_chapters() {
+            return Err(Error::UnsupportedInClass {
+                element: "\\chapter".to_owned(),
+                class: ctx.class().to_string(),
+            });
+        }
+
+        self.0.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        self.0.to_node()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, DocumentType, Text};
+
+    #[test]
+    fn render() {
+        let section = Section::new("Introduction")
+            .short("Intro")
+            .label("sec:intro")
+            .0
+            .with(Text("Hello"))
+            .with(Subsection::new("Details").star());
+
+        assert_eq!(
+            "\\section[Intro]{Introduction}\\label{sec:intro}\nHello\\subsection*{Details}",
+            section.render()
+        );
+    }
+
+    #[test]
+    fn starred_short() {
+        let doc = Document::new().with(Section::new("Introduction").short("Intro").star());
+        assert_eq!(
+            Err(Error::StarredShortTitle("\\section*".to_owned())),
+            doc.try_render()
+        );
+    }
+
+    #[test]
+    fn chapter_requires_class() {
+        let doc = Document::new().with(Chapter::new("One"));
+        assert_eq!(
+            Err(Error::UnsupportedInClass {
+                element: "\\chapter".to_owned(),
+                class: "article".to_owned(),
+            }),
+            doc.try_render()
+        );
+
+        let mut doc = Document::new();
+        doc.preambule().r#type(DocumentType::Book);
+        let doc = doc.with(Chapter::new("One"));
+        assert!(doc.try_render().is_ok());
+
+        let mut doc = Document::new();
+        doc.preambule().r#type(DocumentType::custom("acmart"));
+        let doc = doc.with(Chapter::new("One"));
+        assert!(doc.try_render().is_err());
+
+        let mut doc = Document::new();
+        doc.preambule().r#type(DocumentType::Custom {
+            name: "thesis".to_owned(),
+            chapters: true,
+            frames: false,
+        });
+        let doc = doc.with(Chapter::new("One"));
+        assert!(doc.try_render().is_ok());
+    }
+}
@@ -0,0 +1,482 @@
+use std::fmt;
+
+use crate::float::{Float, Position};
+use crate::node::{Node, TableCell, TableRow};
+use crate::{Container, Context, Element, Error, Label, Macros, Package, Parameter, Raw};
+
+/// A column of a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Column {
+    /// `l`
+    Left,
+    /// `c`
+    Center,
+    /// `r`
+    Right,
+    /// `p{width}`, a paragraph column of the given width, e.g. `3cm`
+    Paragraph(String),
+    /// `|`, a vertical line between columns
+    Separator,
+}
+
+impl Column {
+    fn is_separator(&self) -> bool {
+        matches!(self, Column::Separator)
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Column::Left => f.write_str("l"),
+            Column::Center => f.write_str("c"),
+            Column::Right => f.write_str("r"),
+            Column::Paragraph(width) => write!(f, "p{{{}}}", width),
+            Column::Separator => f.write_str("|"),
+        }
+    }
+}
+
+fn spec(columns: &[Column]) -> String {
+    columns.iter().map(|c| c.to_string()).collect()
+}
+
+/// A cell of a table row.
+pub struct Cell<'a> {
+    content: Box<dyn Element + 'a>,
+    columns: Option<(usize, Vec<Column>)>,
+    rows: Option<usize>,
+}
+
+impl<'a> Cell<'a> {
+    pub fn new<E: Element + 'a>(content: E) -> Self {
+        Self {
+            content: Box::new(content),
+            columns: None,
+            rows: None,
+        }
+    }
+
+    /// Spans the cell over `n` columns with `\multicolumn`,
+    /// the cell is formatted according to `spec`, which must have exactly
+    /// one column besides separators.
+    pub fn columns<I>(mut self, n: usize, spec: I) -> Self
+    where
+        I: IntoIterator<Item = Column>,
+    {
+        self.columns = Some((n, spec.into_iter().collect()));
+        self
+    }
+
+    /// Spans the cell over `n` rows with `\multirow`.
+    ///
+    /// The rows below must have an empty cell in place of it.
+    pub fn rows(mut self, n: usize) -> Self {
+        self.rows = Some(n);
+        self
+    }
+
+    fn width(&self) -> usize {
+        self.columns.as_ref().map_or(1, |(n, _)| *n)
+    }
+
+    fn to_table_cell(&self) -> TableCell {
+        let (columns, spec) = match &self.columns {
+            Some((n, spec)) => (Some(*n), spec.clone()),
+            None => (None, Vec::new()),
+        };
+
+        TableCell {
+            columns,
+            spec,
+            rows: self.rows,
+            body: crate::node::flatten(self.content.to_node()),
+        }
+    }
+}
+
+impl Element for Cell<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        if let Some((n, columns)) = &self.columns {
+            write!(w, "\\multicolumn{{{}}}{{{}}}{{", n, spec(columns))?;
+        }
+        if let Some(n) = self.rows {
+            write!(w, "\\multirow{{{}}}{{*}}{{", n)?;
+        }
+
+        self.content.render_to(w)?;
+
+        if self.rows.is_some() {
+            w.write_char('}')?;
+        }
+        if self.columns.is_some() {
+            w.write_char('}')?;
+        }
+
+        Ok(())
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        if let Some((_, columns)) = &self.columns {
+            if columns.iter().filter(|c| !c.is_separator()).count() != 1 {
+                return Err(Error::IllegalCellSpec(spec(columns)));
+            }
+        }
+        if self.rows.is_some() {
+            ctx.require(Package::new("multirow"))?;
+        }
+
+        self.content.visit(ctx)
+    }
+}
+
+/// A row of a table, each element added with [`Container::with`] is a cell.
+pub struct Row<'a> {
+    cells: Vec<Cell<'a>>,
+}
+
+impl<'a> Row<'a> {
+    pub fn new() -> Self {
+        Self { cells: Vec::new() }
+    }
+
+    pub fn cell(mut self, cell: Cell<'a>) -> Self {
+        self.cells.push(cell);
+        self
+    }
+
+    fn width(&self) -> usize {
+        self.cells.iter().map(|c| c.width()).sum()
+    }
+}
+
+impl Default for Row<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for Row<'a> {
+    fn with<E: Element + 'a>(self, e: E) -> Self {
+        self.cell(Cell::new(e))
+    }
+}
+
+impl Element for Row<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        for (i, cell) in self.cells.iter().enumerate() {
+            if i > 0 {
+                w.write_str(" & ")?;
+            }
+            cell.render_to(w)?;
+        }
+
+        w.write_str(" \\\\")
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        self.cells.iter().try_for_each(|c| c.visit(ctx))
+    }
+}
+
+enum Line<'a> {
+    Row(Row<'a>),
+    Rule(&'static str),
+}
+
+/// A `tabular` environment.
+///
+/// Every row must cover exactly the declared columns,
+/// which is checked when the document is rendered.
+pub struct Tabular<'a> {
+    columns: Vec<Column>,
+    lines: Vec<Line<'a>>,
+}
+
+impl<'a> Tabular<'a> {
+    pub fn new<I>(columns: I) -> Self
+    where
+        I: IntoIterator<Item = Column>,
+    {
+        Self {
+            columns: columns.into_iter().collect(),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn row(mut self, row: Row<'a>) -> Self {
+        self.lines.push(Line::Row(row));
+        self
+    }
+
+    /// `\toprule` of `booktabs`.
+    pub fn toprule(self) -> Self {
+        self.rule("toprule")
+    }
+
+    /// `\midrule` of `booktabs`.
+    pub fn midrule(self) -> Self {
+        self.rule("midrule")
+    }
+
+    /// `\bottomrule` of `booktabs`.
+    pub fn bottomrule(self) -> Self {
+        self.rule("bottomrule")
+    }
+
+    pub fn hline(self) -> Self {
+        self.rule("hline")
+    }
+
+    fn rule(mut self, rule: &'static str) -> Self {
+        self.lines.push(Line::Rule(rule));
+        self
+    }
+
+    /// The number of columns, separators aside.
+    pub fn width(&self) -> usize {
+        self.columns.iter().filter(|c| !c.is_separator()).count()
+    }
+}
+
+impl Element for Tabular<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        Macros::new("begin")
+            .param("tabular")
+            .param(Raw(spec(&self.columns)))
+            .render_to(w)?;
+        for line in &self.lines {
+            w.write_char('\n')?;
+            match line {
+                Line::Row(row) => row.render_to(w)?,
+                Line::Rule(rule) => Macros::new(rule).render_to(w)?,
+            }
+        }
+        w.write_char('\n')?;
+        Macros::new("end").param("tabular").render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        let mut n = 0;
+        for line in &self.lines {
+            match line {
+                Line::Row(row) => {
+                    n += 1;
+                    if row.width() != self.width() {
+                        return Err(Error::ColumnMismatch {
+                            row: n,
+                            expected: self.width(),
+                            found: row.width(),
+                        });
+                    }
+
+                    row.visit(ctx)?;
+                }
+                Line::Rule("hline") => {}
+                Line::Rule(_) => ctx.require(Package::new("booktabs"))?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn to_node(&self) -> Node {
+        let rows = self
+            .lines
+            .iter()
+            .map(|line| match line {
+                Line::Row(row) => TableRow::Row {
+                    cells: row.cells.iter().map(Cell::to_table_cell).collect(),
+                },
+                Line::Rule("toprule") => TableRow::Toprule,
+                Line::Rule("midrule") => TableRow::Midrule,
+                Line::Rule("bottomrule") => TableRow::Bottomrule,
+                Line::Rule(_) => TableRow::Hline,
+            })
+            .collect();
+
+        Node::Tabular {
+            columns: self.columns.clone(),
+            rows,
+        }
+    }
+}
+
+/// A floating `table` with a caption.
+pub struct Table<'a>(Float<'a>);
+
+impl Table<'_> {
+    pub fn new() -> Self {
+        Self(Float::new("table", true))
+    }
+
+    pub fn placement(mut self, positions: &[Position]) -> Self {
+        self.0.placement(positions);
+        self
+    }
+
+    pub fn caption<P: Into<Parameter>>(mut self, caption: P) -> Self {
+        self.0.caption(caption.into());
+        self
+    }
+
+    /// Sets a caption used in the list of tables.
+    pub fn short<P: Into<Parameter>>(mut self, caption: P) -> Self {
+        self.0.short(caption.into());
+        self
+    }
+
+    /// Labels the table, references are made with the returned label.
+    /// It goes with the caption, a label without one is an error.
+    pub fn label<S: AsRef<str>>(mut self, key: S) -> (Self, Label) {
+        let label = Label::new(key);
+        self.0.label(label.clone());
+        (self, label)
+    }
+}
+
+impl Default for Table<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Container<'a> for Table<'a> {
+    fn with<E: Element + 'a>(mut self, e: E) -> Self {
+        self.0 = self.0.with(e);
+        self
+    }
+}
+
+impl Element for Table<'_> {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.0.render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        self.0.visit(ctx)
+    }
+
+    fn to_node(&self) -> Node {
+        self.0.to_node()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, Text};
+
+    fn tabular<'a>() -> Tabular<'a> {
+        Tabular::new(vec![
+            Column::Left,
+            Column::Separator,
+            Column::Center,
+            Column::Paragraph("2cm".to_owned()),
+        ])
+        .toprule()
+        .row(
+            Row::new()
+                .with(Text("Name"))
+                .cell(Cell::new(Text("Score")).columns(2, vec![Column::Center])),
+        )
+        .midrule()
+        .row(
+            Row::new()
+                .cell(Cell::new(Text("a_b")).rows(2))
+                .with(Text("1"))
+                .with(Text("2")),
+        )
+        .row(Row::new().with(Text("")).with(Text("3")).with(Text("4")))
+        .bottomrule()
+    }
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            "\\begin{tabular}{l|cp{2cm}}
+\\toprule
+Name & \\multicolumn{2}{c}{Score} \\\\
+\\midrule
+\\multirow{2}{*}{a\\_b} & 1 & 2 \\\\
+ & 3 & 4 \\\\
+\\bottomrule
+\\end{tabular}",
+            tabular().render()
+        );
+
+        let table = Table::new()
+            .placement(&[Position::Here, Position::Top])
+            .caption("Scores")
+            .label("tab:scores")
+            .0
+            .with(Tabular::new(vec![Column::Left]).row(Row::new().with(Text("x"))));
+        assert_eq!(
+            "\\begin{table}[ht]
+\\centering
+\\caption{Scores}\\label{tab:scores}
+\\begin{tabular}{l}
+x \\\\
+\\end{tabular}
+\\end{table}",
+            table.render()
+        );
+    }
+
+    #[test]
+    fn registers_packages() {
+        let doc =
+            Document::new().with(Table::new().placement(&[Position::Exactly]).with(tabular()));
+        let rendered = doc.try_render().unwrap();
+        assert!(rendered.starts_with(
+            "\\documentclass{article}\n\\usepackage{float}\n\\usepackage{booktabs}\n\\usepackage{multirow}\n"
+        ));
+
+        let doc = Document::new().with(
+            Table::new()
+                .placement(&[Position::Here, Position::Exactly])
+                .with(tabular()),
+        );
+        assert_eq!(
+            Err(Error::IllegalPlacement("hH".to_owned())),
+            doc.try_render()
+        );
+    }
+
+    #[test]
+    fn validates_columns() {
+        let tabular = Tabular::new(vec![Column::Left, Column::Right])
+            .row(Row::new().with(Text("a")).with(Text("b")))
+            .row(Row::new().with(Text("a")));
+
+        assert_eq!(
+            Err(Error::ColumnMismatch {
+                row: 2,
+                expected: 2,
+                found: 1
+            }),
+            Document::new().with(tabular).try_render()
+        );
+
+        let cell = |spec: Vec<Column>| {
+            let tabular = Tabular::new(vec![Column::Left, Column::Right])
+                .row(Row::new().cell(Cell::new(Text("a")).columns(2, spec)));
+            Document::new().with(tabular).try_render()
+        };
+        assert!(cell(vec![Column::Separator, Column::Center, Column::Separator]).is_ok());
+        assert_eq!(
+            Err(Error::IllegalCellSpec("lr".to_owned())),
+            cell(vec![Column::Left, Column::Right])
+        );
+        assert_eq!(
+            Err(Error::IllegalCellSpec("|".to_owned())),
+            cell(vec![Column::Separator])
+        );
+        assert!(cell(Vec::new()).is_err());
+    }
+}
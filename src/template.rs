@@ -0,0 +1,458 @@
+//! Filling `.tex` skeletons with elements.
+//!
+//! A placeholder is `%%name%%`, a block repeated for each item
+//! of an iterator is `%%#name%%..%%/name%%`. A block tag alone
+//! on its line takes the line with it. A tag starts a comment to LaTeX,
+//! so a skeleton whose tags end their lines compiles before it's filled.
+//! An escaped `\%` isn't a delimiter, other text which only looks
+//! like a tag, e.g. `100%% sure`, is left as it is. Tags in comments
+//! are filled too.
+//!
+//! ```
+//! use trylatex::template::{Template, Values};
+//! use trylatex::Macros;
+//!
+//! let template = Template::parse(
+//!     "\\title{%%title%%
+//! }
+//! \\begin{itemize}
+//! %%#items%%
+//! \\item %%name%%: %%value%%
+//! %%/items%%
+//! \\end{itemize}",
+//! )
+//! .unwrap();
+//!
+//! let values = Values::new().text("title", "Q1 & Q2").block(
+//!     "items",
+//!     vec![("a", 1), ("b", 2)].into_iter().map(|(name, value)| {
+//!         Values::new()
+//!             .element("name", Macros::new("textbf").param(name))
+//!             .text("value", value.to_string())
+//!     }),
+//! );
+//!
+//! assert_eq!(
+//!     "\\title{Q1 \\& Q2
+//! }
+//! \\begin{itemize}
+//! \\item \\textbf{a}: 1
+//! \\item \\textbf{b}: 2
+//! \\end{itemize}",
+//!     template.render(&values).unwrap()
+//! );
+//! ```
+
+use std::{fmt, fs, io, path::Path};
+
+use crate::{Element, IoWriter, ParseError, TemplateError, Text};
+
+const DELIMITER: &str = "%%";
+
+enum Part {
+    Text(String),
+    Value(String),
+    Block { name: String, body: Vec<Part> },
+}
+
+/// A parsed skeleton.
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(src: &str) -> Result<Self, ParseError> {
+        let mut parts = Vec::new();
+        // blocks which aren't closed yet
+        let mut open: Vec<(String, Vec<Part>, usize)> = Vec::new();
+        let mut text = String::new();
+
+        let mut rest = 0;
+        let mut i = 0;
+        while let Some(found) = src[i..].find(DELIMITER) {
+            let start = i + found;
+            let tag = match tag(src, start) {
+                Some(tag) => tag,
+                None => {
+                    i = start + 1;
+                    continue;
+                }
+            };
+            text.push_str(&src[rest..start]);
+            let mut end = tag.end;
+
+            if tag.kind != Kind::Value {
+                // a tag alone on its line takes the line with it
+                let blank = |c: char| c == ' ' || c == '\t';
+                let before = src[..start].trim_end_matches(blank);
+                let line_end = src[end..].trim_start_matches(blank);
+                if (before.is_empty() || before.ends_with('\n'))
+                    && (line_end.is_empty() || line_end.starts_with('\n'))
+                {
+                    text.truncate(text.len() - (start - before.len()));
+                    end = src.len() - line_end.len() + line_end.starts_with('\n') as usize;
+                }
+            }
+
+            let current = match open.last_mut() {
+                Some((_, body, _)) => body,
+                None => &mut parts,
+            };
+            if !text.is_empty() {
+                current.push(Part::Text(std::mem::take(&mut text)));
+            }
+
+            match tag.kind {
+                Kind::Value => current.push(Part::Value(tag.name.to_owned())),
+                Kind::Open => open.push((tag.name.to_owned(), Vec::new(), start)),
+                Kind::Close => match open.pop() {
+                    Some((name, body, _)) if name == tag.name => {
+                        let block = Part::Block { name, body };
+                        match open.last_mut() {
+                            Some((_, body, _)) => body.push(block),
+                            None => parts.push(block),
+                        }
+                    }
+                    _ => {
+                        return Err(error(
+                            src,
+                            start,
+                            format!("block {} is closed but isn't open", tag.name),
+                        ))
+                    }
+                },
+            }
+
+            rest = end;
+            i = end;
+        }
+
+        if let Some((name, _, start)) = open.pop() {
+            return Err(error(src, start, format!("block {} isn't closed", name)));
+        }
+
+        text.push_str(&src[rest..]);
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Reads and parses a skeleton, a malformed one is an
+    /// [`io::ErrorKind::InvalidData`] error which wraps [`ParseError`].
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let src = fs::read_to_string(path)?;
+        Self::parse(&src).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Checks every placeholder has a value of its kind
+    /// and every value has a placeholder.
+    pub fn check(&self, values: &Values) -> Result<(), TemplateError> {
+        fill(&self.parts, &mut vec![values], None)
+    }
+
+    pub fn render(&self, values: &Values) -> Result<String, TemplateError> {
+        self.check(values)?;
+
+        let mut buf = String::new();
+        fill(&self.parts, &mut vec![values], Some(&mut buf))?;
+        Ok(buf)
+    }
+
+    /// Checks the values like [`Template::render`] and streams the result,
+    /// invalid values are an [`io::ErrorKind::InvalidData`] error
+    /// which wraps [`TemplateError`].
+    pub fn write_to(&self, values: &Values, w: &mut dyn io::Write) -> io::Result<()> {
+        self.check(values)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let mut w = IoWriter::new(w);
+        let result = fill(&self.parts, &mut vec![values], Some(&mut w)).map_err(|err| match err {
+            TemplateError::Fmt(err) => err,
+            _ => unreachable!("the values are checked"),
+        });
+        w.finish(result)
+    }
+}
+
+/// Values of placeholders.
+///
+/// Inside a block the values of an item come first,
+/// then the ones of enclosing blocks and the template.
+#[derive(Default)]
+pub struct Values<'a> {
+    values: Vec<(String, Value<'a>)>,
+}
+
+enum Value<'a> {
+    Element(Box<dyn Element + 'a>),
+    Block(Vec<Values<'a>>),
+}
+
+impl<'a> Values<'a> {
+    pub fn new() -> Self {
+        Self { values: Vec::new() }
+    }
+
+    pub fn element<S, E>(self, name: S, element: E) -> Self
+    where
+        S: AsRef<str>,
+        E: Element + 'a,
+    {
+        self.set(name, Value::Element(Box::new(element)))
+    }
+
+    /// Sets plain text, LaTeX special characters are escaped.
+    pub fn text<S, T>(self, name: S, text: T) -> Self
+    where
+        S: AsRef<str>,
+        T: AsRef<str> + 'a,
+    {
+        self.element(name, Text(text))
+    }
+
+    /// Sets the items a block is repeated for.
+    pub fn block<S, I>(self, name: S, items: I) -> Self
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = Values<'a>>,
+    {
+        self.set(name, Value::Block(items.into_iter().collect()))
+    }
+
+    fn set<S: AsRef<str>>(mut self, name: S, value: Value<'a>) -> Self {
+        let name = name.as_ref();
+        match self.values.iter_mut().find(|(n, _)| n == name) {
+            Some((_, present)) => *present = value,
+            None => self.values.push((name.to_owned(), value)),
+        }
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<&Value<'a>> {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Fills the parts, only checking the values without a writer.
+fn fill<'v, 'w>(
+    parts: &[Part],
+    scopes: &mut Vec<&'v Values<'v>>,
+    mut w: Option<&mut (dyn fmt::Write + 'w)>,
+) -> Result<(), TemplateError> {
+    if let Some(unused) = scopes.last().and_then(|values| {
+        values
+            .values
+            .iter()
+            .find(|(name, _)| !mentions(parts, name))
+    }) {
+        return Err(TemplateError::Unused(unused.0.clone()));
+    }
+
+    for part in parts {
+        match part {
+            Part::Text(text) => {
+                if let Some(w) = w.as_deref_mut() {
+                    w.write_str(text)?;
+                }
+            }
+            Part::Value(name) => match lookup(scopes, name) {
+                Some(Value::Element(element)) => {
+                    if let Some(w) = w.as_deref_mut() {
+                        element.render_to(w)?;
+                    }
+                }
+                Some(Value::Block(_)) => return Err(TemplateError::ExpectedValue(name.clone())),
+                None => return Err(TemplateError::Missing(name.clone())),
+            },
+            Part::Block { name, body } => match lookup(scopes, name) {
+                Some(Value::Block(items)) => {
+                    for item in items {
+                        scopes.push(item);
+                        let result = fill(body, scopes, w.as_deref_mut());
+                        scopes.pop();
+                        result?;
+                    }
+                }
+                Some(Value::Element(_)) => return Err(TemplateError::ExpectedBlock(name.clone())),
+                None => return Err(TemplateError::Missing(name.clone())),
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn lookup<'v>(scopes: &[&'v Values<'v>], name: &str) -> Option<&'v Value<'v>> {
+    scopes.iter().rev().find_map(|values| values.get(name))
+}
+
+fn mentions(parts: &[Part], name: &str) -> bool {
+    parts.iter().any(|part| match part {
+        Part::Text(_) => false,
+        Part::Value(n) => n == name,
+        Part::Block { name: n, body } => n == name || mentions(body, name),
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Kind {
+    Value,
+    Open,
+    Close,
+}
+
+struct Tag<'s> {
+    kind: Kind,
+    name: &'s str,
+    end: usize,
+}
+
+/// Reads a tag starting at the delimiter, text which only looks like one isn't a tag.
+fn tag(src: &str, start: usize) -> Option<Tag<'_>> {
+    // `\%` is a percent sign, `\\%` a line break then a comment
+    let backslashes = src[..start].len() - src[..start].trim_end_matches('\\').len();
+    if backslashes % 2 == 1 {
+        return None;
+    }
+
+    let mut s = &src[start + DELIMITER.len()..];
+    let kind = match s.chars().next() {
+        Some('#') => Kind::Open,
+        Some('/') => Kind::Close,
+        _ => Kind::Value,
+    };
+    if kind != Kind::Value {
+        s = &s[1..];
+    }
+
+    let len = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'))
+        .unwrap_or(s.len());
+    if len == 0 || !s[len..].starts_with(DELIMITER) {
+        return None;
+    }
+
+    Some(Tag {
+        kind,
+        name: &s[..len],
+        end: src.len() - s.len() + len + DELIMITER.len(),
+    })
+}
+
+fn error<S: Into<String>>(src: &str, at: usize, message: S) -> ParseError {
+    let before = &src[..at];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    ParseError {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Raw, Section};
+
+    #[test]
+    fn fill() {
+        let template = Template::parse(
+            r"% 100%% sure, %%not a tag%% \%%note%%
+\multicolumn{1}{c}{{Total}}
+\section{%%title%%}
+  %%#rows%%
+%%#cells%%%%cell%% & %%/cells%%%%total%% \\
+  %%/rows%%
+50\%%%unit%% \\%%unit%%
+%%body%%",
+        )
+        .unwrap();
+
+        let values = Values::new()
+            .text("title", "A_B")
+            .text("unit", "x")
+            .element("body", Section::new("Next"))
+            .block(
+                "rows",
+                (1..=2).map(|i| {
+                    Values::new()
+                        .block(
+                            "cells",
+                            vec![Values::new().element("cell", Raw(i.to_string()))],
+                        )
+                        .text("total", (i * 10).to_string())
+                }),
+            );
+
+        assert_eq!(
+            r"% 100%% sure, %%not a tag%% \%%note%%
+\multicolumn{1}{c}{{Total}}
+\section{A\_B}
+1 & 10 \\
+2 & 20 \\
+50\%x \\x
+\section{Next}",
+            template.render(&values).unwrap()
+        );
+
+        let mut buf = Vec::new();
+        template.write_to(&values, &mut buf).unwrap();
+        assert_eq!(template.render(&values).unwrap().as_bytes(), &buf[..]);
+    }
+
+    #[test]
+    fn errors() {
+        let template = Template::parse("%%a%% %%#b%%%%c%%%%/b%%").unwrap();
+        let block = || vec![Values::new().text("c", "")];
+
+        assert_eq!(
+            Err(TemplateError::Missing("a".to_owned())),
+            template.check(&Values::new().block("b", block()))
+        );
+        assert_eq!(
+            Err(TemplateError::Missing("c".to_owned())),
+            template.check(&Values::new().text("a", "").block("b", vec![Values::new()]))
+        );
+        assert_eq!(
+            Err(TemplateError::Unused("d".to_owned())),
+            template.check(
+                &Values::new()
+                    .text("a", "")
+                    .block("b", block())
+                    .text("d", "")
+            )
+        );
+        assert_eq!(
+            Err(TemplateError::ExpectedBlock("b".to_owned())),
+            template.check(&Values::new().text("a", "").text("b", ""))
+        );
+        assert_eq!(
+            Err(TemplateError::ExpectedValue("a".to_owned())),
+            template.check(&Values::new().block("a", block()).block("b", block()))
+        );
+        // values of the template can be used in blocks
+        assert!(template
+            .check(
+                &Values::new()
+                    .text("a", "")
+                    .text("c", "")
+                    .block("b", vec![Values::new()])
+            )
+            .is_ok());
+
+        assert_eq!(
+            Some("2:3: block b isn't closed".to_owned()),
+            Template::parse("\n  %%#b%%").err().map(|e| e.to_string())
+        );
+        assert_eq!(
+            Some("1:1: block b is closed but isn't open".to_owned()),
+            Template::parse("%%/b%%").err().map(|e| e.to_string())
+        );
+    }
+}
@@ -0,0 +1,672 @@
+//! Drawings with TikZ, `tikzpicture`.
+//!
+//! ```
+//! use trylatex::tikz::{Anchor, Coord, Node, Path, Style, TikzPicture};
+//! use trylatex::{Container, Document};
+//!
+//! let mut doc = Document::new();
+//! doc.preambule().tikz_library("positioning");
+//!
+//! let doc = doc.with(
+//!     TikzPicture::new()
+//!         .define("service", Style::new().draw().option("rounded corners"))
+//!         .node(Node::new("API").name("api").style(Style::new().option("service")))
+//!         .node(Node::new("DB").name("db").at(Coord::xy(3.0, 0.0)).style(Style::new().option("service")))
+//!         .path(Path::draw(Coord::anchor("api", Anchor::East)).style(Style::new().arrow("->")).line_to(Coord::anchor("db", Anchor::West))),
+//! );
+//! assert!(doc.try_render().unwrap().contains("\\draw[->] (api.east) -- (db.west);"));
+//! ```
+
+use std::fmt;
+
+use crate::{Argument, Context, Element, Error, Macros, Package, Parameter};
+
+/// A point on the shape of a node, e.g. `(a.north east)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    Center,
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+    /// The base line of the text.
+    Base,
+    /// The point of the border in the direction, in degrees.
+    Angle(f64),
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anchor::Center => f.write_str("center"),
+            Anchor::North => f.write_str("north"),
+            Anchor::NorthEast => f.write_str("north east"),
+            Anchor::East => f.write_str("east"),
+            Anchor::SouthEast => f.write_str("south east"),
+            Anchor::South => f.write_str("south"),
+            Anchor::SouthWest => f.write_str("south west"),
+            Anchor::West => f.write_str("west"),
+            Anchor::NorthWest => f.write_str("north west"),
+            Anchor::Base => f.write_str("base"),
+            Anchor::Angle(angle) => write!(f, "{}", angle),
+        }
+    }
+}
+
+/// A position on the picture.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Coord {
+    /// `(x,y)`, in centimeters.
+    Cartesian { x: f64, y: f64 },
+    /// `(angle:radius)`, the angle in degrees and the radius in centimeters.
+    Polar { angle: f64, radius: f64 },
+    /// `(name)` or `(name.anchor)` of a node or a named coordinate.
+    Node {
+        name: String,
+        anchor: Option<Anchor>,
+    },
+    /// `++(..)`, from the previous point of the path, which moves to the result.
+    Relative(Box<Coord>),
+    /// `+(..)`, from the previous point of the path, which stays where it was.
+    Shifted(Box<Coord>),
+}
+
+impl Coord {
+    pub fn xy(x: f64, y: f64) -> Self {
+        Coord::Cartesian { x, y }
+    }
+
+    pub fn polar(angle: f64, radius: f64) -> Self {
+        Coord::Polar { angle, radius }
+    }
+
+    pub fn node<S: AsRef<str>>(name: S) -> Self {
+        Coord::Node {
+            name: name.as_ref().to_owned(),
+            anchor: None,
+        }
+    }
+
+    pub fn anchor<S: AsRef<str>>(name: S, anchor: Anchor) -> Self {
+        Coord::Node {
+            name: name.as_ref().to_owned(),
+            anchor: Some(anchor),
+        }
+    }
+
+    /// Makes the coordinate relative, `++`.
+    pub fn relative(self) -> Self {
+        Coord::Relative(Box::new(self.absolute()))
+    }
+
+    /// Makes the coordinate relative without moving, `+`.
+    pub fn shifted(self) -> Self {
+        Coord::Shifted(Box::new(self.absolute()))
+    }
+
+    fn absolute(self) -> Self {
+        match self {
+            Coord::Relative(c) | Coord::Shifted(c) => *c,
+            c => c,
+        }
+    }
+
+    /// The node the coordinate refers to, if any.
+    fn name(&self) -> Option<&str> {
+        match self {
+            Coord::Node { name, .. } => Some(name),
+            Coord::Relative(c) | Coord::Shifted(c) => c.name(),
+            Coord::Cartesian { .. } | Coord::Polar { .. } => None,
+        }
+    }
+}
+
+impl From<(f64, f64)> for Coord {
+    fn from((x, y): (f64, f64)) -> Self {
+        Coord::xy(x, y)
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Coord::Cartesian { x, y } => write!(f, "({},{})", x, y),
+            Coord::Polar { angle, radius } => write!(f, "({}:{})", angle, radius),
+            Coord::Node { name, anchor: None } => write!(f, "({})", name),
+            Coord::Node {
+                name,
+                anchor: Some(anchor),
+            } => write!(f, "({}.{})", name, anchor),
+            Coord::Relative(c) => write!(f, "++{}", c),
+            Coord::Shifted(c) => write!(f, "+{}", c),
+        }
+    }
+}
+
+/// Options of a picture, a node or a path, e.g. `[draw, fill=blue!20]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style {
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Style {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `key`, e.g. `thick` or a style defined with [`TikzPicture::define`].
+    pub fn option<S: AsRef<str>>(mut self, key: S) -> Self {
+        self.options.push((key.as_ref().to_owned(), None));
+        self
+    }
+
+    /// `key=value`, replacing an earlier value of the key.
+    pub fn value<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let (key, value) = (key.as_ref(), Some(value.as_ref().to_owned()));
+        match self
+            .options
+            .iter_mut()
+            .find(|(k, v)| k == key && v.is_some())
+        {
+            Some(present) => present.1 = value,
+            None => self.options.push((key.to_owned(), value)),
+        }
+        self
+    }
+
+    pub fn draw(self) -> Self {
+        self.option("draw")
+    }
+
+    pub fn fill<S: AsRef<str>>(self, color: S) -> Self {
+        self.value("fill", color)
+    }
+
+    pub fn color<S: AsRef<str>>(self, color: S) -> Self {
+        self.value("color", color)
+    }
+
+    /// Arrow tips of a path, e.g. `->` or `<->`.
+    pub fn arrow<S: AsRef<str>>(self, tips: S) -> Self {
+        self.option(tips)
+    }
+
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        if self.options.is_empty() {
+            Ok(())
+        } else {
+            write!(w, "[{}]", self)
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, value)) in self.options.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(key)?;
+            if let Some(value) = value {
+                write!(f, "={}", value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A shape with text, placed on the picture or along a path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    name: Option<String>,
+    at: Option<Coord>,
+    style: Style,
+    text: Parameter,
+}
+
+impl Node {
+    pub fn new<P: Into<Parameter>>(text: P) -> Self {
+        Self {
+            name: None,
+            at: None,
+            style: Style::new(),
+            text: text.into(),
+        }
+    }
+
+    /// Names the node so coordinates can refer to it and its anchors,
+    /// the name can't have `.`, `,`, `(` or `)`.
+    pub fn name<S: AsRef<str>>(mut self, name: S) -> Self {
+        self.name = Some(name.as_ref().to_owned());
+        self
+    }
+
+    /// By default a node is at the origin, or at the point of the path it's on.
+    pub fn at<C: Into<Coord>>(mut self, at: C) -> Self {
+        self.at = Some(at.into());
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// `node[..] (name) at (..) {text}`, without the leading `\`.
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_str("node")?;
+        self.style.render_to(w)?;
+        if let Some(name) = &self.name {
+            write!(w, " ({})", name)?;
+        }
+        if let Some(at) = &self.at {
+            write!(w, " at {}", at)?;
+        }
+        w.write_char(' ')?;
+        Argument::Mandatory(self.text.clone()).render_to(w)
+    }
+}
+
+/// Characters which would make a coordinate of the name ambiguous.
+const RESERVED: &[char] = &['.', ',', '(', ')'];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Move(Coord),
+    Line(Coord),
+    Curve {
+        first: Coord,
+        second: Option<Coord>,
+        to: Coord,
+    },
+    Arc {
+        start: f64,
+        end: f64,
+        radius: f64,
+    },
+    Cycle,
+    Node(Node),
+}
+
+impl Step {
+    fn coords(&self) -> Vec<&Coord> {
+        match self {
+            Step::Move(c) | Step::Line(c) => vec![c],
+            Step::Curve { first, second, to } => {
+                let mut coords = vec![first];
+                coords.extend(second);
+                coords.push(to);
+                coords
+            }
+            Step::Node(node) => node.at.iter().collect(),
+            Step::Arc { .. } | Step::Cycle => Vec::new(),
+        }
+    }
+}
+
+/// A path drawn from a point, `\draw (0,0) -- (1,1);` and alike.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    command: &'static str,
+    style: Style,
+    start: Coord,
+    steps: Vec<Step>,
+}
+
+impl Path {
+    /// `\path`, nothing is drawn unless the style says so.
+    pub fn new<C: Into<Coord>>(start: C) -> Self {
+        Self::with_command("path", start)
+    }
+
+    pub fn draw<C: Into<Coord>>(start: C) -> Self {
+        Self::with_command("draw", start)
+    }
+
+    pub fn fill<C: Into<Coord>>(start: C) -> Self {
+        Self::with_command("fill", start)
+    }
+
+    pub fn filldraw<C: Into<Coord>>(start: C) -> Self {
+        Self::with_command("filldraw", start)
+    }
+
+    fn with_command<C: Into<Coord>>(command: &'static str, start: C) -> Self {
+        Self {
+            command,
+            style: Style::new(),
+            start: start.into(),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Starts a new part of the path without a line to it.
+    pub fn move_to<C: Into<Coord>>(mut self, to: C) -> Self {
+        self.steps.push(Step::Move(to.into()));
+        self
+    }
+
+    /// `-- (..)`
+    pub fn line_to<C: Into<Coord>>(mut self, to: C) -> Self {
+        self.steps.push(Step::Line(to.into()));
+        self
+    }
+
+    /// `.. controls (..) .. (..)`
+    pub fn curve_to<C: Into<Coord>, D: Into<Coord>>(mut self, control: C, to: D) -> Self {
+        self.steps.push(Step::Curve {
+            first: control.into(),
+            second: None,
+            to: to.into(),
+        });
+        self
+    }
+
+    /// `.. controls (..) and (..) .. (..)`, a curve with two control points.
+    pub fn curve_to_with<C, D, E>(mut self, first: C, second: D, to: E) -> Self
+    where
+        C: Into<Coord>,
+        D: Into<Coord>,
+        E: Into<Coord>,
+    {
+        self.steps.push(Step::Curve {
+            first: first.into(),
+            second: Some(second.into()),
+            to: to.into(),
+        });
+        self
+    }
+
+    /// An arc from the current point, the angles in degrees.
+    pub fn arc(mut self, start: f64, end: f64, radius: f64) -> Self {
+        self.steps.push(Step::Arc { start, end, radius });
+        self
+    }
+
+    /// Closes the current part of the path, `-- cycle`.
+    pub fn cycle(mut self) -> Self {
+        self.steps.push(Step::Cycle);
+        self
+    }
+
+    /// Puts a node at the current point, e.g. a label of an edge
+    /// with the `midway` option.
+    pub fn node(mut self, node: Node) -> Self {
+        self.steps.push(Step::Node(node));
+        self
+    }
+
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        write!(w, "\\{}", self.command)?;
+        self.style.render_to(w)?;
+        write!(w, " {}", self.start)?;
+
+        for step in &self.steps {
+            match step {
+                Step::Move(to) => write!(w, " {}", to)?,
+                Step::Line(to) => write!(w, " -- {}", to)?,
+                Step::Curve {
+                    first,
+                    second: None,
+                    to,
+                } => write!(w, " .. controls {} .. {}", first, to)?,
+                Step::Curve {
+                    first,
+                    second: Some(second),
+                    to,
+                } => write!(w, " .. controls {} and {} .. {}", first, second, to)?,
+                Step::Arc { start, end, radius } => write!(
+                    w,
+                    " arc[start angle={}, end angle={}, radius={}]",
+                    start, end, radius
+                )?,
+                Step::Cycle => w.write_str(" -- cycle")?,
+                Step::Node(node) => {
+                    w.write_char(' ')?;
+                    node.render_to(w)?;
+                }
+            }
+        }
+
+        w.write_char(';')
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Node(Node),
+    Coordinate(String, Coord),
+    Path(Path),
+}
+
+/// A drawing, `tikzpicture`.
+///
+/// Coordinates may refer only to nodes defined before them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TikzPicture {
+    styles: Vec<(String, Style)>,
+    style: Style,
+    commands: Vec<Command>,
+}
+
+impl TikzPicture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options of the whole picture.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Defines a named style, `name/.style={..}`.
+    pub fn define<S: AsRef<str>>(mut self, name: S, style: Style) -> Self {
+        self.styles.push((name.as_ref().to_owned(), style));
+        self
+    }
+
+    pub fn node(mut self, node: Node) -> Self {
+        self.commands.push(Command::Node(node));
+        self
+    }
+
+    /// Names a point, `\coordinate (name) at (..);`,
+    /// the name can't have `.`, `,`, `(` or `)` like the one of a node.
+    pub fn coordinate<S: AsRef<str>, C: Into<Coord>>(mut self, name: S, at: C) -> Self {
+        self.commands
+            .push(Command::Coordinate(name.as_ref().to_owned(), at.into()));
+        self
+    }
+
+    pub fn path(mut self, path: Path) -> Self {
+        self.commands.push(Command::Path(path));
+        self
+    }
+}
+
+impl Element for TikzPicture {
+    fn render_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        Macros::new("begin").param("tikzpicture").render_to(w)?;
+
+        if !self.styles.is_empty() || !self.style.options.is_empty() {
+            w.write_char('[')?;
+            for (i, (name, style)) in self.styles.iter().enumerate() {
+                if i > 0 {
+                    w.write_str(", ")?;
+                }
+                write!(w, "{}/.style={{{}}}", name, style)?;
+            }
+            if !self.style.options.is_empty() {
+                if !self.styles.is_empty() {
+                    w.write_str(", ")?;
+                }
+                write!(w, "{}", self.style)?;
+            }
+            w.write_char(']')?;
+        }
+
+        for command in &self.commands {
+            w.write_char('\n')?;
+            match command {
+                Command::Node(node) => {
+                    w.write_char('\\')?;
+                    node.render_to(w)?;
+                    w.write_char(';')?;
+                }
+                Command::Coordinate(name, at) => {
+                    write!(w, "\\coordinate ({}) at {};", name, at)?;
+                }
+                Command::Path(path) => path.render_to(w)?,
+            }
+        }
+
+        w.write_char('\n')?;
+        Macros::new("end").param("tikzpicture").render_to(w)
+    }
+
+    fn visit(&self, ctx: &mut Context) -> Result<(), Error> {
+        ctx.require(Package::new("tikz"))?;
+
+        // names defined so far, a node can be used right after it on its path
+        let mut names: Vec<&str> = Vec::new();
+        let check = |coord: &Coord, names: &[&str]| match coord.name() {
+            Some(name) if !names.contains(&name) => Err(Error::UndefinedNode(name.to_owned())),
+            _ => Ok(()),
+        };
+        for command in &self.commands {
+            match command {
+                Command::Node(node) => {
+                    if let Some(at) = &node.at {
+                        check(at, &names)?;
+                    }
+                    if let Some(name) = &node.name {
+                        names.push(define(name)?);
+                    }
+                }
+                Command::Coordinate(name, at) => {
+                    check(at, &names)?;
+                    names.push(define(name)?);
+                }
+                Command::Path(path) => {
+                    check(&path.start, &names)?;
+                    for step in &path.steps {
+                        for coord in step.coords() {
+                            check(coord, &names)?;
+                        }
+                        if let Step::Node(Node {
+                            name: Some(name), ..
+                        }) = step
+                        {
+                            names.push(define(name)?);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks the name of a node or a coordinate.
+fn define(name: &str) -> Result<&str, Error> {
+    if name.contains(RESERVED) {
+        Err(Error::IllegalNodeName(name.to_owned()))
+    } else {
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Container, Document, Raw};
+
+    #[test]
+    fn render() {
+        let mut doc = Document::new();
+        doc.preambule()
+            .tikz_library("arrows.meta")
+            .tikz_library("positioning");
+        let doc = doc.with(
+            TikzPicture::new()
+                .define("box", Style::new().draw().fill("blue!20"))
+                .style(Style::new().option("thick"))
+                .node(
+                    Node::new("A & B")
+                        .name("a")
+                        .style(Style::new().option("box")),
+                )
+                .node(Node::new(Raw("$x$")).name("b").at(Coord::polar(30.0, 2.5)))
+                .coordinate("c", (1.0, -0.5))
+                .path(
+                    Path::draw(Coord::anchor("a", Anchor::NorthEast))
+                        .style(Style::new().arrow("-Stealth").color("red").color("gray"))
+                        .line_to(Coord::xy(1.0, 0.0).relative())
+                        .curve_to(Coord::node("c"), Coord::anchor("b", Anchor::Angle(45.0)))
+                        .node(Node::new("calls").style(Style::new().option("midway")))
+                        .move_to(Coord::xy(0.0, 1.0).shifted().relative())
+                        .curve_to_with((1.0, 1.0), (2.0, 1.0), (2.0, 0.0))
+                        .arc(0.0, 180.0, 0.5)
+                        .cycle(),
+                ),
+        );
+
+        assert_eq!(
+            r"\documentclass{article}
+\usepackage{tikz}
+\usetikzlibrary{arrows.meta,positioning}
+
+\begin{document}
+\begin{tikzpicture}[box/.style={draw, fill=blue!20}, thick]
+\node[box] (a) {A \& B};
+\node (b) at (30:2.5) {$x$};
+\coordinate (c) at (1,-0.5);
+\draw[-Stealth, color=gray] (a.north east) -- ++(1,0) .. controls (c) .. (b.45) node[midway] {calls} ++(0,1) .. controls (1,1) and (2,1) .. (2,0) arc[start angle=0, end angle=180, radius=0.5] -- cycle;
+\end{tikzpicture}
+\end{document}
+",
+            doc.try_render().unwrap()
+        );
+    }
+
+    #[test]
+    fn undefined_node() {
+        let doc = Document::new().with(
+            TikzPicture::new()
+                .path(Path::draw((0.0, 0.0)).line_to(Coord::node("a").relative()))
+                .node(Node::new("a").name("a")),
+        );
+        assert_eq!(Err(Error::UndefinedNode("a".to_owned())), doc.try_render());
+
+        // a node on a path is defined for the rest of the path
+        let doc = Document::new().with(
+            TikzPicture::new().path(
+                Path::draw((0.0, 0.0))
+                    .node(Node::new("").name("n"))
+                    .line_to(Coord::anchor("n", Anchor::East)),
+            ),
+        );
+        assert!(doc.try_render().is_ok());
+
+        let doc = Document::new().with(TikzPicture::new().coordinate("a.b", (0.0, 0.0)));
+        assert_eq!(
+            Err(Error::IllegalNodeName("a.b".to_owned())),
+            doc.try_render()
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::{fmt, io};
+
+/// Lets elements be rendered into an [`io::Write`].
+///
+/// [`fmt::Error`] doesn't carry anything, so the [`io::Error`]
+/// which caused it is kept until [`IoWriter::finish`].
+pub struct IoWriter<W: io::Write> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> IoWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Turns the result of rendering back into an [`io::Result`].
+    pub fn finish(mut self, result: fmt::Result) -> io::Result<()> {
+        match (result, self.error.take()) {
+            (_, Some(err)) => Err(err),
+            (Err(_), None) => Err(io::Error::other("an element failed to render")),
+            (Ok(()), None) => self.inner.flush(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Container, Document, Element, Label, Ref, Section, Text};
+
+    /// Accepts a few bytes and fails afterwards.
+    struct Short(Vec<u8>);
+
+    impl io::Write for Short {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.0.len() + buf.len() > 16 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+            }
+            self.0.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_to() {
+        let doc = Document::new()
+            .with(Section::new("A & B"))
+            .with(Text("100%"));
+
+        let mut buf = Vec::new();
+        doc.write_to(&mut buf).unwrap();
+        assert_eq!(doc.render().as_bytes(), &buf[..]);
+
+        let err = doc.write_to(&mut Short(Vec::new())).unwrap_err();
+        assert_eq!(io::ErrorKind::WriteZero, err.kind());
+    }
+
+    #[test]
+    fn try_write_to() {
+        let doc = Document::new().with(Ref::new(&Label::new("missing")));
+
+        let err = doc.try_write_to(&mut Vec::new()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            Some(&crate::Error::UndefinedLabel("missing".to_owned())),
+            err.get_ref().and_then(|err| err.downcast_ref())
+        );
+
+        // an invalid document isn't written without the packages it needs
+        let err = doc.write_to(&mut Vec::new()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(doc.render_to(&mut String::new()).is_err());
+    }
+}